@@ -0,0 +1,167 @@
+use rand::{seq::SliceRandom, Rng};
+use std::fmt;
+
+use crate::{
+    cards::{Board, Status},
+    engine::{self, Action},
+    view::PlayerView,
+};
+
+// Number of board-deck orders sampled for each estimate
+pub const DEFAULT_SAMPLES: usize = 2000;
+
+// Copies of each value from 1 to 10 in a fresh board deck
+const COPIES_PER_VALUE: u8 = 4;
+
+// How many of each value from 1 to 10 are left in the board deck, counted from the
+// cards already dealt onto both boards rather than from the deck's hidden order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeckCounts(pub [u8; 10]);
+
+impl DeckCounts {
+    pub fn from_view(view: &PlayerView) -> DeckCounts {
+        let mut counts = [COPIES_PER_VALUE; 10];
+
+        for value in view.board.drawn.iter().chain(&view.opponent_board.drawn) {
+            if let Some(count) = counts.get_mut((*value as usize).wrapping_sub(1)) {
+                *count = count.saturating_sub(1);
+            }
+        }
+
+        DeckCounts(counts)
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().map(|&count| count as usize).sum()
+    }
+
+    // Chance that the next card drawn, added to `total`, gives a total matching `predicate`
+    fn draw_chance(&self, total: i8, predicate: impl Fn(i8) -> bool) -> f64 {
+        let cards = self.total();
+        if cards == 0 {
+            return 0.0;
+        }
+
+        let matching: usize = (1..=10)
+            .filter(|&value| predicate(total + value))
+            .map(|value| self.0[value as usize - 1] as usize)
+            .sum();
+        matching as f64 / cards as f64
+    }
+
+    fn cards(&self) -> Vec<i8> {
+        (1..=10)
+            .flat_map(|value| std::iter::repeat_n(value, self.0[value as usize - 1] as usize))
+            .collect()
+    }
+}
+
+// What the advisor expects from one way of playing the turn
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    // The card to play, or None for playing no card
+    pub action: Option<Action>,
+    // The board total once the card is played
+    pub total: i8,
+    // Chance the next card drawn takes the total over 20
+    pub bust_chance: f64,
+    // Chance the next card drawn makes the total exactly 20
+    pub twenty_chance: f64,
+    // Estimated chance of winning the set by standing straight after the play
+    pub stand_win_chance: f64,
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "total {}, next draw busts {:.0}%, reaches 20 {:.0}%, standing wins {:.0}%",
+            self.total,
+            self.bust_chance * 100.0,
+            self.twenty_chance * 100.0,
+            self.stand_win_chance * 100.0
+        )
+    }
+}
+
+// Whether a total beats the opponent's once both are final. `tiebreaker` is whether
+// only our side of the table holds a TieBreaker, which wins a tie.
+fn beats(total: i8, opponent_total: i8, tiebreaker: bool) -> bool {
+    opponent_total > 20 || total > opponent_total || (total == opponent_total && tiebreaker)
+}
+
+// Estimates the chance of winning the set by standing on `board`. A standing opponent's
+// board is final. Otherwise the opponent is sampled drawing from the board deck until they
+// match or pass our total, or bust, ignoring the side cards we can't see.
+fn stand_win_chance<R: Rng + ?Sized>(
+    board: &Board,
+    view: &PlayerView,
+    deck: &DeckCounts,
+    samples: usize,
+    rng: &mut R,
+) -> f64 {
+    let total = board.total();
+    if total > 20 {
+        return 0.0;
+    }
+
+    let tiebreaker = board.has_tiebreaker() && !view.opponent_board.has_tiebreaker();
+    let final_chance = |opponent_total| {
+        if beats(total, opponent_total, tiebreaker) {
+            1.0
+        } else {
+            0.0
+        }
+    };
+
+    let mut cards = deck.cards();
+    if view.opponent_status != Status::Playing || cards.is_empty() || samples == 0 {
+        return final_chance(view.opponent_board.total());
+    }
+
+    let mut wins = 0.0;
+    for _ in 0..samples {
+        cards.shuffle(rng);
+
+        // Every turn starts with a draw, so the opponent always takes at least one card
+        let mut opponent_total = view.opponent_board.total();
+        for value in &cards {
+            opponent_total += value;
+            if opponent_total >= total {
+                break;
+            }
+        }
+
+        wins += final_chance(opponent_total);
+    }
+
+    wins / samples as f64
+}
+
+// Advises on every way the view's seat can play its turn: first playing no card, then
+// every card in its hand played every way it can be, in hand order
+pub fn advise<R: Rng + ?Sized>(view: &PlayerView, samples: usize, rng: &mut R) -> Vec<Hint> {
+    let deck = DeckCounts::from_view(view);
+    let mut plays = vec![None];
+    if !view.played_card {
+        plays.extend(engine::card_plays(&view.hand).into_iter().map(Some));
+    }
+
+    plays
+        .into_iter()
+        .map(|action| {
+            let board = match action {
+                Some(action) => engine::board_after(&view.board, &view.hand, action),
+                None => view.board.clone(),
+            };
+            let total = board.total();
+            Hint {
+                action,
+                total,
+                bust_chance: deck.draw_chance(total, |total| total > 20),
+                twenty_chance: deck.draw_chance(total, |total| total == 20),
+                stand_win_chance: stand_win_chance(&board, view, &deck, samples, rng),
+            }
+        })
+        .collect()
+}
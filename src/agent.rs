@@ -0,0 +1,135 @@
+use crate::{
+    cards::{SpecialType, Status},
+    engine::{self, Action},
+    solver::SolverAgent,
+    view::PlayerView,
+};
+
+// Anything that can pick actions for a seat without a human at the keyboard
+pub trait Agent {
+    fn name(&self) -> &str;
+
+    // Called repeatedly during the agent's turn until it stands or ends the turn
+    fn choose_action(&self, view: &PlayerView) -> Action;
+
+    // Restarts any random choices the agent makes from `seed`, so that matches it plays
+    // can be replayed exactly. Agents that never choose at random can ignore it.
+    fn reseed(&mut self, _seed: u64) {}
+}
+
+// Looks up a built-in agent by the name used on the command line, e.g. "ai:basic"
+pub fn from_name(name: &str) -> Option<Box<dyn Agent>> {
+    match name {
+        "ai:basic" => Some(Box::new(BasicAgent::default())),
+        "ai:solver" => Some(Box::new(SolverAgent)),
+        _ => None,
+    }
+}
+
+// A card play the agent could make this turn and the board total it would lead to
+struct Play {
+    action: Action,
+    total: i8,
+    is_tiebreaker: bool,
+}
+
+// Every card in the view's hand, played every way it can be
+fn possible_plays(view: &PlayerView) -> Vec<Play> {
+    engine::card_plays(&view.hand)
+        .into_iter()
+        .map(|action| {
+            let is_tiebreaker = match action {
+                Action::Play { card_index, .. } => {
+                    view.hand[card_index].special_type == SpecialType::TieBreaker
+                }
+                _ => false,
+            };
+
+            Play {
+                action,
+                total: engine::board_after(&view.board, &view.hand, action).total(),
+                is_tiebreaker,
+            }
+        })
+        .collect()
+}
+
+// Stands once it reaches a threshold, uses side cards to hit 20 exactly or to
+// get back under 20 after a bad draw, and keeps drawing while a standing opponent is ahead
+pub struct BasicAgent {
+    pub stand_threshold: i8,
+}
+
+impl Default for BasicAgent {
+    fn default() -> Self {
+        BasicAgent {
+            stand_threshold: 17,
+        }
+    }
+}
+
+impl Agent for BasicAgent {
+    fn name(&self) -> &str {
+        "ai:basic"
+    }
+
+    fn choose_action(&self, view: &PlayerView) -> Action {
+        let total = view.board.total();
+        let opponent_total = view.opponent_board.total();
+        let opponent_standing = view.opponent_status == Status::Standing;
+
+        if !view.played_card && total != 20 {
+            let plays = possible_plays(view);
+
+            // Hitting 20 exactly is always worth a card, a TieBreaker more so
+            if let Some(play) = plays
+                .iter()
+                .filter(|play| play.total == 20)
+                .max_by_key(|play| play.is_tiebreaker)
+            {
+                return play.action;
+            }
+
+            // Rescue a bust with whichever card gets closest to 20
+            if total > 20 {
+                if let Some(play) = plays
+                    .iter()
+                    .filter(|play| play.total <= 20)
+                    .max_by_key(|play| play.total)
+                {
+                    return play.action;
+                }
+            }
+
+            // Catch up to a standing opponent rather than drawing into them
+            if opponent_standing && total < opponent_total {
+                if let Some(play) = plays
+                    .iter()
+                    .filter(|play| play.total >= opponent_total && play.total <= 20)
+                    .max_by_key(|play| (play.total, play.is_tiebreaker))
+                {
+                    return play.action;
+                }
+            }
+        }
+
+        if total > 20 {
+            return Action::EndTurn;
+        }
+
+        if opponent_standing {
+            // Only stand once we are not behind the opponent
+            if total > opponent_total || (total == opponent_total && total >= self.stand_threshold)
+            {
+                return Action::Stand;
+            }
+            return Action::EndTurn;
+        }
+
+        if total >= self.stand_threshold {
+            Action::Stand
+        } else {
+            Action::EndTurn
+        }
+    }
+}
@@ -1,17 +1,25 @@
 use crossterm::style::Stylize;
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
+use rand_chacha::ChaCha12Rng;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-use crate::util::SPECIAL_CARD_REGEXES;
+use crate::{
+    engine::{GamePhase, PlayerId},
+    rules::{HandPolicy, Rules},
+    util::SPECIAL_CARD_REGEXES,
+};
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum SpecialType {
     None,
     Flip,
     Invert,
     Double,
     TieBreaker,
+    // KOTOR II's gold card, played as +1, -1, +2 or -2
+    OneOrTwo,
 }
 
 impl fmt::Display for SpecialType {
@@ -22,16 +30,26 @@ impl fmt::Display for SpecialType {
             SpecialType::Invert => write!(f, "Invert"),
             SpecialType::Double => write!(f, "Double"),
             SpecialType::TieBreaker => write!(f, "TieBreaker"),
+            SpecialType::OneOrTwo => write!(f, "OneOrTwo"),
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Card {
     pub values_list: Vec<i8>,
     pub value: i8,
     pub special_type: SpecialType,
-    pub board_effect: Option<fn(&mut Board, &mut Card)>,
+    pub effect: Option<CardEffect>,
+}
+
+// What a special card does to the board when it is played, interpreted by `engine::apply_effect`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardEffect {
+    // Flips the sign of every card on the board worth plus or minus a value in the list
+    InvertValues(Vec<i8>),
+    // The played card takes the value of the last card on the board that isn't an Invert
+    DoubleLast,
 }
 
 impl Card {
@@ -40,7 +58,7 @@ impl Card {
             values_list: vec![value],
             value,
             special_type: SpecialType::None,
-            board_effect: None,
+            effect: None,
         }
     }
 
@@ -48,6 +66,28 @@ impl Card {
         self.value = self.values_list[index];
     }
 
+    // The plain text form of the card, as accepted by `Card::from_string`
+    pub fn notation(&self) -> String {
+        match self.special_type {
+            SpecialType::None => self.values_list[0].to_string(),
+            SpecialType::Flip => format!("{:+}/{:+}", self.values_list[0], self.values_list[1]),
+            SpecialType::Invert => format!("{}&{}", self.values_list[0], self.values_list[1]),
+            SpecialType::Double => "D".to_string(),
+            SpecialType::TieBreaker => {
+                format!("{:+}/{:+}T", self.values_list[0], self.values_list[1])
+            }
+            SpecialType::OneOrTwo => "+/-1/2".to_string(),
+        }
+    }
+
+    // Whether the player has to choose which value to play this card with
+    pub fn has_playstyles(&self) -> bool {
+        matches!(
+            self.special_type,
+            SpecialType::Flip | SpecialType::TieBreaker | SpecialType::OneOrTwo
+        )
+    }
+
     pub fn from_string(card_string: &str) -> Option<Card> {
         // Check each regex for a match
         // If a match is found, create a card based on the regex
@@ -65,53 +105,46 @@ impl Card {
                 match card_type {
                     SpecialType::None => {
                         // Create a card based on the regex
+                        // Values that don't fit a card, like "+999", make the card unreadable
                         let value = regex_string
-                            .captures(card_string)
-                            .unwrap()
-                            .get(1)
-                            .unwrap()
+                            .captures(card_string)?
+                            .get(1)?
                             .as_str()
                             .parse::<i8>()
-                            .unwrap();
+                            .ok()?;
 
                         return Some(Card::new(value));
                     }
                     SpecialType::Flip => {
                         // Create a card based on the two groups in the first match
-                        let captures = regex_string.captures(card_string).unwrap();
-                        let values: Vec<i8> = captures
+                        let captures = regex_string.captures(card_string)?;
+                        let values = captures
                             .iter()
                             .skip(1)
-                            .map(|x| x.unwrap().as_str().parse::<i8>().unwrap())
-                            .collect();
+                            .map(|x| x?.as_str().parse::<i8>().ok())
+                            .collect::<Option<Vec<i8>>>()?;
 
                         return Some(Card {
                             values_list: values,
                             value: 0,
                             special_type: *card_type,
-                            board_effect: None,
+                            effect: None,
                         });
                     }
                     SpecialType::Invert => {
                         // Create a card based on the regex
-                        let captures = regex_string.captures(card_string).unwrap();
-                        let values: Vec<i8> = captures
+                        let captures = regex_string.captures(card_string)?;
+                        let values = captures
                             .iter()
                             .skip(1)
-                            .map(|x| x.unwrap().as_str().parse::<i8>().unwrap())
-                            .collect();
+                            .map(|x| x?.as_str().parse::<i8>().ok())
+                            .collect::<Option<Vec<i8>>>()?;
 
                         return Some(Card {
+                            effect: Some(CardEffect::InvertValues(values.clone())),
                             values_list: values,
                             value: 0,
                             special_type: *card_type,
-                            board_effect: Some(|board, played_card| {
-                                for card in &mut board.cards {
-                                    if played_card.values_list.contains(&card.value) {
-                                        card.value *= -1;
-                                    }
-                                }
-                            }),
                         });
                     }
                     SpecialType::Double => {
@@ -120,28 +153,30 @@ impl Card {
                             values_list: vec![0],
                             value: 0,
                             special_type: *card_type,
-                            board_effect: Some(|board, played_card| {
-                                // Find out what the last card played was on the board
-                                if let Some(last_card) = board.cards.last() {
-                                    // If the last card played was a double, play the card again
-                                    played_card.value = last_card.value;
-                                }
-                            }),
+                            effect: Some(CardEffect::DoubleLast),
+                        });
+                    }
+                    SpecialType::OneOrTwo => {
+                        return Some(Card {
+                            values_list: vec![1, -1, 2, -2],
+                            value: 0,
+                            special_type: *card_type,
+                            effect: None,
                         });
                     }
                     SpecialType::TieBreaker => {
-                        let captures = regex_string.captures(card_string).unwrap();
-                        let values: Vec<i8> = captures
+                        let captures = regex_string.captures(card_string)?;
+                        let values = captures
                             .iter()
                             .skip(1)
-                            .map(|x| x.unwrap().as_str().parse::<i8>().unwrap())
-                            .collect();
+                            .map(|x| x?.as_str().parse::<i8>().ok())
+                            .collect::<Option<Vec<i8>>>()?;
 
                         return Some(Card {
                             values_list: values,
                             value: 0,
                             special_type: *card_type,
-                            board_effect: None,
+                            effect: None,
                         });
                     }
                 }
@@ -213,13 +248,23 @@ impl fmt::Display for Card {
 
                 card_string = format!("{}T", formatted_values).blue().to_string();
             }
+            SpecialType::OneOrTwo => {
+                // Show the value it was played as once it is on the board
+                if self.value != 0 {
+                    card_string.push_str(&format!("{:+}[±1/2]", self.value));
+                } else {
+                    card_string.push_str("±1/2");
+                }
+
+                card_string = card_string.yellow().to_string();
+            }
         }
 
         write!(f, "{}", card_string)
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Deck {
     pub cards: Vec<Card>,
 }
@@ -229,9 +274,9 @@ impl Deck {
         Deck { cards: vec![] }
     }
 
-    pub fn shuffle(&mut self) {
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         // Shuffle the deck
-        self.cards.shuffle(&mut rand::thread_rng());
+        self.cards.shuffle(rng);
     }
 
     pub fn draw(&mut self) -> Option<Card> {
@@ -246,7 +291,7 @@ impl Deck {
                     values_list: vec![i],
                     value: i,
                     special_type: SpecialType::None,
-                    board_effect: None,
+                    effect: None,
                 })
             })
             .collect::<Vec<_>>();
@@ -272,7 +317,7 @@ impl fmt::Display for Deck {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Hand {
     pub cards: Vec<Card>,
 }
@@ -313,30 +358,58 @@ impl fmt::Display for Hand {
     }
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Status {
     Playing,
     Standing,
     Busted,
 }
 
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Playing => write!(f, "Playing"),
+            Status::Standing => write!(f, "Standing"),
+            Status::Busted => write!(f, "Busted"),
+        }
+    }
+}
+
 // player takes a mutable deck
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
     pub hand: Hand,
     pub deck: Deck,
     pub status: Status,
 }
-#[derive(Clone)]
+// Number of cards that fill a player's side of the table
+pub const TABLE_SIZE: usize = 9;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Board {
     pub cards: Vec<Card>,
+    // The value of every card dealt onto the board from the board deck, in order, so
+    // what is left in the deck can be counted from the table without knowing its order
+    #[serde(default)]
+    pub drawn: Vec<i8>,
 }
 
 impl Board {
+    // Puts a card dealt from the board deck onto the board
+    pub fn draw(&mut self, card: Card) {
+        self.drawn.push(card.value);
+        self.cards.push(card);
+    }
+
     pub fn total(&self) -> i8 {
         self.cards.iter().map(|c| c.value).sum()
     }
 
+    // A board is filled once it holds a full table of cards without going over 20
+    pub fn is_filled(&self) -> bool {
+        self.cards.len() >= TABLE_SIZE && self.total() <= 20
+    }
+
     pub fn has_tiebreaker(&self) -> bool {
         self.cards
             .iter()
@@ -361,34 +434,48 @@ impl fmt::Display for Board {
     }
 }
 // A Game is a collection of players, boards, and a deck
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Game {
     pub board: [Board; 2],
     pub deck: Deck,
     pub turn: u8,
-    pub winner: u8,
+    pub winner: Option<usize>,
+    // Whether the active player has already played a card this turn
+    pub played_card: bool,
 }
 
 impl Game {
-    pub fn new() -> Game {
-        let board1 = Board { cards: vec![] };
-        let board2 = Board { cards: vec![] };
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> Game {
+        let board1 = Board::default();
+        let board2 = Board::default();
 
         // Generate Game Deck
         let mut board_deck = Deck::new();
         board_deck.default_fill();
-        board_deck.shuffle();
+        board_deck.shuffle(rng);
 
         Game {
             board: [board1, board2],
             deck: board_deck,
             turn: 1,
-            winner: 0,
+            winner: None,
+            played_card: false,
         }
     }
 
+    // The player who filled their side of the table without busting, if any
+    pub fn filled_table(&self) -> Option<usize> {
+        (0..2).find(|&player| self.board[player].is_filled())
+    }
+
     // Check which player won the game by comparing the total of their boards and seeing who didn't bust
-    pub fn check_win(&mut self) -> Option<usize> {
+    pub fn check_win(&self, rules: &Rules) -> Option<usize> {
+        if rules.fill_the_table {
+            if let Some(player) = self.filled_table() {
+                return Some(player);
+            }
+        }
+
         let player1_total = self.board[0].total();
         let player2_total = self.board[1].total();
 
@@ -417,53 +504,113 @@ impl Game {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Match {
     pub games: Vec<Game>,
     pub players: [Player; 2],
     pub match_detail: MatchDetails,
+    // Source of randomness for every board deck shuffled during the match
+    pub rng: ChaCha12Rng,
+    #[serde(default)]
+    pub rules: Rules,
+    // Moved on by the engine, which checks every call against it
+    pub phase: GamePhase,
+    // Whose turn starts next while the match is Dealing. The first player opens every set.
+    pub next_player: PlayerId,
 }
 
-impl Match {
-    pub fn new(mut deck1: Deck, mut deck2: Deck) -> Match {
-        let mut player_hand = Hand::new();
-        let mut opponent_hand = Hand::new();
+// Number of side-deck cards dealt into a hand
+pub const HAND_SIZE: usize = 4;
 
-        for _ in 0..4 {
-            player_hand.cards.push(deck1.draw().unwrap());
-            opponent_hand.cards.push(deck2.draw().unwrap());
-        }
+// A player's side deck ran out before their hand could be dealt in full
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShortDeal {
+    pub player: usize,
+    pub dealt: usize,
+    pub wanted: usize,
+}
 
+impl fmt::Display for ShortDeal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Player {}'s side deck ran out, dealt {} of {} cards",
+            self.player + 1,
+            self.dealt,
+            self.wanted
+        )
+    }
+}
+
+impl Match {
+    // Hands are dealt by `new_game`, according to `Rules::hand_policy`
+    pub fn new(deck1: Deck, deck2: Deck, rng: ChaCha12Rng) -> Match {
         Match {
             games: vec![],
             players: [
                 Player {
-                    hand: player_hand,
+                    hand: Hand::new(),
                     deck: deck1,
                     status: Status::Playing,
                 },
                 Player {
-                    hand: opponent_hand,
+                    hand: Hand::new(),
                     deck: deck2,
                     status: Status::Playing,
                 },
             ],
             match_detail: MatchDetails::new(),
+            rng,
+            rules: Rules::default(),
+            phase: GamePhase::Dealing,
+            next_player: 0,
         }
     }
 
-    pub fn new_game(&mut self) {
-        let new_game = Game::new();
+    // Starts the next set, dealing hands if the hand policy calls for it.
+    // Returns every player whose side deck could not fill their hand.
+    pub fn new_game(&mut self) -> Vec<ShortDeal> {
+        let new_game = Game::new(&mut self.rng);
 
         // Reset the players' statuses
         self.players[0].status = Status::Playing;
         self.players[1].status = Status::Playing;
 
+        let deal = match self.rules.hand_policy {
+            HandPolicy::KeepForMatch => self.games.is_empty(),
+            HandPolicy::DealEachSet => true,
+        };
+
+        let mut short_deals = vec![];
+        if deal {
+            for (i, player) in self.players.iter_mut().enumerate() {
+                // Unplayed cards from the last set are discarded
+                player.hand.cards.clear();
+
+                while player.hand.cards.len() < HAND_SIZE {
+                    match player.deck.draw() {
+                        Some(card) => player.hand.cards.push(card),
+                        None => break,
+                    }
+                }
+
+                if player.hand.cards.len() < HAND_SIZE {
+                    short_deals.push(ShortDeal {
+                        player: i,
+                        dealt: player.hand.cards.len(),
+                        wanted: HAND_SIZE,
+                    });
+                }
+            }
+        }
+
         // add the game to the match
         self.games.push(new_game);
 
         // Increment the round
         self.match_detail.round += 1;
+
+        short_deals
     }
 
     pub fn current_game(&mut self) -> &mut Game {
@@ -505,7 +652,7 @@ impl fmt::Display for Match {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct MatchDetails {
     pub round: usize,
     pub score: [u8; 2],
@@ -0,0 +1,63 @@
+use pazaak::net::{ClientMessage, Connection, HostMessage, NetError};
+use pazaak::PlayerView;
+
+use crate::ui::{Choice, Ui};
+
+// Plays our seat of a match hosted on another machine, until it ends or we quit
+pub fn play_remote(mut connection: Connection, ui: &mut dyn Ui) -> Result<(), NetError> {
+    // The last table we were shown, to show again if the host ends the match early
+    let mut last_view: Option<PlayerView> = None;
+
+    loop {
+        // The host may wait on its own player for as long as they like
+        match connection.receive(None)? {
+            HostMessage::Log(message) => ui.log(&message),
+            HostMessage::Turn(view) => {
+                ui.show_turn(&view);
+                last_view = Some(view);
+            }
+            HostMessage::Board(view) => {
+                ui.show_board(&view);
+                last_view = Some(view);
+            }
+            HostMessage::Prompt(view) => {
+                let action = loop {
+                    match ui.take_action(&view) {
+                        Choice::Act(action) => break Some(action),
+                        Choice::Save(_) => ui.log("Only the host can save the match."),
+                        Choice::Nothing => {}
+                        Choice::Quit => break None,
+                    }
+                };
+
+                match action {
+                    Some(action) => connection.send(&ClientMessage::Action(action))?,
+                    None => {
+                        // We are leaving either way, so a failed goodbye doesn't matter
+                        let _ = connection.send(&ClientMessage::Quit);
+                        return Ok(());
+                    }
+                }
+                last_view = Some(view);
+            }
+            HostMessage::SetOver { view, message } => {
+                ui.show_result(&view, &message);
+                last_view = Some(view);
+            }
+            HostMessage::MatchOver { view, message } => {
+                ui.show_match_end(&view, &message);
+                return Ok(());
+            }
+            HostMessage::Closed(reason) => {
+                match &last_view {
+                    Some(view) => ui.show_match_end(view, &reason),
+                    None => ui.log(&reason),
+                }
+                return Ok(());
+            }
+            HostMessage::Welcome { .. } | HostMessage::Rejected(_) => {
+                return Err(NetError::UnexpectedMessage)
+            }
+        }
+    }
+}
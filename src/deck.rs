@@ -0,0 +1,628 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path};
+
+use crate::cards::{Card, Deck, SpecialType, HAND_SIZE};
+
+// Number of cards a side deck must hold
+pub const SIDE_DECK_SIZE: usize = 10;
+
+// Most copies of one card a deck file may ask for, as many cards as a Side Deck holds
+pub const MAX_CARD_COUNT: u8 = SIDE_DECK_SIZE as u8;
+
+// Bumped whenever the layout of a .pzk file changes. Files without a
+// "pazaak-deck" header line are version 1, one card per line.
+pub const DECK_FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug)]
+pub enum DeckError {
+    Io(io::Error),
+    UnparsableLine { line_no: usize, text: String },
+    // A card in a JSON or TOML deck that isn't in `Card::from_string` notation
+    UnparsableCard(String),
+    // A JSON or TOML deck that couldn't be read or written
+    Format(String),
+    UnknownFormat(String),
+    // Every way the deck breaks the deck rules, not just the first
+    Invalid(Vec<DeckViolation>),
+    UnsupportedVersion(u32),
+    // A card in the preferred opening that the deck doesn't hold enough of
+    OpeningNotInDeck { card: String },
+    OpeningTooLarge { found: usize },
+    // A name or author a .pzk file can't hold, as it starts a comment or ends the line
+    UnwritableDetail { key: &'static str, value: String },
+}
+
+// A single way a deck breaks the `DeckRules` it was checked against
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeckViolation {
+    // The card at this 1-based position is not in the card pool at all
+    NotInPool {
+        position: usize,
+        card: String,
+    },
+    QuotaExceeded {
+        kind: CardKind,
+        limit: u8,
+        found: u8,
+    },
+    WrongSize {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for DeckViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeckViolation::NotInPool { position, card } => {
+                write!(
+                    f,
+                    "Card {} '{}' is not allowed in a side deck",
+                    position, card
+                )
+            }
+            DeckViolation::QuotaExceeded { kind, limit, found } => write!(
+                f,
+                "Too many '{}' cards, at most {} allowed but found {}",
+                kind, limit, found
+            ),
+            DeckViolation::WrongSize { expected, found } => {
+                write!(f, "Deck must hold {} cards but holds {}", expected, found)
+            }
+        }
+    }
+}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeckError::Io(error) => write!(f, "Unable to read deck: {}", error),
+            DeckError::UnparsableLine { line_no, text } => {
+                write!(f, "Invalid card on line {}: '{}'", line_no, text)
+            }
+            DeckError::UnparsableCard(card) => write!(f, "Invalid card: '{}'", card),
+            DeckError::Format(error) => write!(f, "Invalid deck: {}", error),
+            DeckError::UnknownFormat(path) => write!(
+                f,
+                "Unknown deck format for '{}', expected a .pzk, .json or .toml file",
+                path
+            ),
+            DeckError::Invalid(violations) => {
+                let messages = violations
+                    .iter()
+                    .map(|violation| violation.to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "{}", messages.join("\n"))
+            }
+            DeckError::UnsupportedVersion(version) => write!(
+                f,
+                "Deck format version {} is not supported, expected at most {}",
+                version, DECK_FORMAT_VERSION
+            ),
+            DeckError::OpeningNotInDeck { card } => {
+                write!(f, "Opening card '{}' is not in the deck", card)
+            }
+            DeckError::OpeningTooLarge { found } => write!(
+                f,
+                "The opening can hold at most {} cards but holds {}",
+                HAND_SIZE, found
+            ),
+            DeckError::UnwritableDetail { key, value } => write!(
+                f,
+                "The deck's {} {:?} can't hold a '#' or a line break",
+                key, value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeckError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DeckError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DeckError {
+    fn from(error: io::Error) -> Self {
+        DeckError::Io(error)
+    }
+}
+
+// A family of cards in the side-deck card pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardKind {
+    // Plain cards with a value between `min` and `max`, use negative bounds for '-' cards
+    Standard { min: i8, max: i8 },
+    // '+X/-X' cards with X between `min` and `max`
+    Flip { min: i8, max: i8 },
+    // 'X&Y' cards with exactly these two values
+    Invert(i8, i8),
+    Double,
+    // '+X/-XT' cards with X between `min` and `max`
+    TieBreaker { min: i8, max: i8 },
+    OneOrTwo,
+}
+
+// Whether a two-valued card is a '+X/-X' pair with X in range
+fn is_mirrored_pair(values: &[i8], min: i8, max: i8) -> bool {
+    match values {
+        [a, b] => *a == -*b && (min..=max).contains(&a.abs()),
+        _ => false,
+    }
+}
+
+impl CardKind {
+    pub fn matches(&self, card: &Card) -> bool {
+        let values = card.values_list.as_slice();
+
+        match (*self, card.special_type) {
+            (CardKind::Standard { min, max }, SpecialType::None) => {
+                (min..=max).contains(&values[0])
+            }
+            (CardKind::Flip { min, max }, SpecialType::Flip) => is_mirrored_pair(values, min, max),
+            (CardKind::Invert(a, b), SpecialType::Invert) => values == [a, b] || values == [b, a],
+            (CardKind::Double, SpecialType::Double) => true,
+            (CardKind::TieBreaker { min, max }, SpecialType::TieBreaker) => {
+                is_mirrored_pair(values, min, max)
+            }
+            (CardKind::OneOrTwo, SpecialType::OneOrTwo) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for CardKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CardKind::Standard { min, max } if min == max => write!(f, "{:+}", min),
+            CardKind::Standard { min, max } => write!(f, "{:+} to {:+}", min, max),
+            CardKind::Flip { min, max } if min == max => write!(f, "+{}/-{}", min, min),
+            CardKind::Flip { min, max } => write!(f, "+/-{} to +/-{}", min, max),
+            CardKind::Invert(a, b) => write!(f, "{}&{}", a, b),
+            CardKind::Double => write!(f, "D"),
+            CardKind::TieBreaker { min, max } if min == max => {
+                write!(f, "+{}/-{}T", min, min)
+            }
+            CardKind::TieBreaker { min, max } => write!(f, "+/-{}T to +/-{}T", min, max),
+            CardKind::OneOrTwo => write!(f, "+/-1/2"),
+        }
+    }
+}
+
+// A kind of card in the pool and how many of it a single deck may hold
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolEntry {
+    pub kind: CardKind,
+    pub limit: u8,
+}
+
+// The card pool side decks are built from and the size they must be
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeckRules {
+    pub deck_size: usize,
+    pub pool: Vec<PoolEntry>,
+}
+
+impl Default for DeckRules {
+    fn default() -> Self {
+        DeckRules::kotor()
+    }
+}
+
+impl DeckRules {
+    // The side-deck pool from KOTOR. Each set of 12 '+', '-' and '+/-' cards ranging 1-6
+    // holds 2 of each value.
+    pub fn kotor() -> DeckRules {
+        let entry = |kind, limit| PoolEntry { kind, limit };
+
+        let mut pool = vec![];
+        for value in 1..=6 {
+            pool.push(entry(
+                CardKind::Standard {
+                    min: value,
+                    max: value,
+                },
+                2,
+            ));
+            pool.push(entry(
+                CardKind::Standard {
+                    min: -value,
+                    max: -value,
+                },
+                2,
+            ));
+            pool.push(entry(
+                CardKind::Flip {
+                    min: value,
+                    max: value,
+                },
+                2,
+            ));
+        }
+        pool.extend([
+            entry(CardKind::Invert(2, 4), 2),
+            entry(CardKind::Invert(3, 6), 2),
+            entry(CardKind::OneOrTwo, 1),
+            entry(CardKind::Double, 1),
+            entry(CardKind::TieBreaker { min: 1, max: 1 }, 1),
+        ]);
+
+        DeckRules {
+            deck_size: SIDE_DECK_SIZE,
+            pool,
+        }
+    }
+
+    // Checks the deck against the pool, returning every violation found
+    pub fn validate(&self, deck: &Deck) -> Result<(), DeckError> {
+        let mut violations = vec![];
+        let mut counts = vec![0u8; self.pool.len()];
+
+        for (i, card) in deck.cards.iter().enumerate() {
+            match self.pool.iter().position(|entry| entry.kind.matches(card)) {
+                Some(entry_index) => counts[entry_index] = counts[entry_index].saturating_add(1),
+                None => violations.push(DeckViolation::NotInPool {
+                    position: i + 1,
+                    card: card.notation(),
+                }),
+            }
+        }
+
+        for (entry, found) in self.pool.iter().zip(counts) {
+            if found > entry.limit {
+                violations.push(DeckViolation::QuotaExceeded {
+                    kind: entry.kind,
+                    limit: entry.limit,
+                    found,
+                });
+            }
+        }
+
+        if deck.cards.len() != self.deck_size {
+            violations.push(DeckViolation::WrongSize {
+                expected: self.deck_size,
+                found: deck.cards.len(),
+            });
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(DeckError::Invalid(violations))
+        }
+    }
+}
+
+// A side deck as read from a .pzk file, with the details the file holds besides its cards.
+// In JSON and TOML it is written as a `DeckDocument`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "DeckDocument", into = "DeckDocument")]
+pub struct DeckFile {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub deck: Deck,
+    // Cards the player would like in their opening hand, in the order they are dealt
+    pub opening: Vec<Card>,
+}
+
+impl DeckFile {
+    // Shuffles a copy of the deck, then puts the preferred opening on top of it
+    pub fn shuffled<R: Rng + ?Sized>(&self, rng: &mut R) -> Deck {
+        let mut deck = self.deck.clone();
+        deck.shuffle(rng);
+
+        let mut opening = vec![];
+        for card in &self.opening {
+            if let Some(i) = deck.cards.iter().position(|c| c == card) {
+                opening.push(deck.cards.remove(i));
+            }
+        }
+
+        // Cards are drawn from the end, so the first opening card goes last
+        deck.cards.extend(opening.into_iter().rev());
+        deck
+    }
+
+    // Checks that every opening card can be dealt from the deck
+    pub fn check_opening(&self) -> Result<(), DeckError> {
+        if self.opening.len() > HAND_SIZE {
+            return Err(DeckError::OpeningTooLarge {
+                found: self.opening.len(),
+            });
+        }
+
+        let mut remaining = self.deck.cards.clone();
+        for card in &self.opening {
+            match remaining.iter().position(|c| c == card) {
+                Some(i) => {
+                    remaining.remove(i);
+                }
+                None => {
+                    return Err(DeckError::OpeningNotInDeck {
+                        card: card.notation(),
+                    })
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Checks that the name and author can be written to a .pzk file and read back as they are
+    pub fn check_details(&self) -> Result<(), DeckError> {
+        for (key, value) in [("name", &self.name), ("author", &self.author)] {
+            if let Some(value) = value {
+                if value.contains(['#', '\n', '\r']) {
+                    return Err(DeckError::UnwritableDetail {
+                        key,
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Writes the canonical version 2 form of the file. Comments are not kept.
+impl fmt::Display for DeckFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "pazaak-deck {}", DECK_FORMAT_VERSION)?;
+        if let Some(name) = &self.name {
+            writeln!(f, "name: {}", name)?;
+        }
+        if let Some(author) = &self.author {
+            writeln!(f, "author: {}", author)?;
+        }
+
+        // Identical cards are counted together, in the order they first appear
+        let mut counts: Vec<(String, usize)> = vec![];
+        for card in &self.deck.cards {
+            let notation = card.notation();
+            match counts.iter_mut().find(|(n, _)| *n == notation) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((notation, 1)),
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(f, "[cards]")?;
+        for (notation, count) in counts {
+            match count {
+                1 => writeln!(f, "{}", notation)?,
+                _ => writeln!(f, "{}x {}", count, notation)?,
+            }
+        }
+
+        if !self.opening.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "[opening]")?;
+            for card in &self.opening {
+                writeln!(f, "{}", card.notation())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// The JSON and TOML layout of a `DeckFile`, with every card in the notation read by
+// `Card::from_string` and repeated cards listed once per copy
+#[derive(Serialize, Deserialize)]
+struct DeckDocument {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    cards: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    opening: Vec<String>,
+}
+
+fn notations(cards: &[Card]) -> Vec<String> {
+    cards.iter().map(|card| card.notation()).collect()
+}
+
+fn cards_from_notations(notations: &[String]) -> Result<Vec<Card>, DeckError> {
+    notations
+        .iter()
+        .map(|notation| {
+            Card::from_string(notation).ok_or_else(|| DeckError::UnparsableCard(notation.clone()))
+        })
+        .collect()
+}
+
+impl From<DeckFile> for DeckDocument {
+    fn from(deck_file: DeckFile) -> Self {
+        DeckDocument {
+            version: Some(DECK_FORMAT_VERSION),
+            name: deck_file.name,
+            author: deck_file.author,
+            cards: notations(&deck_file.deck.cards),
+            opening: notations(&deck_file.opening),
+        }
+    }
+}
+
+impl TryFrom<DeckDocument> for DeckFile {
+    type Error = DeckError;
+
+    fn try_from(document: DeckDocument) -> Result<Self, Self::Error> {
+        if let Some(version) = document.version {
+            if version == 0 || version > DECK_FORMAT_VERSION {
+                return Err(DeckError::UnsupportedVersion(version));
+            }
+        }
+
+        let deck_file = DeckFile {
+            name: document.name,
+            author: document.author,
+            deck: Deck {
+                cards: cards_from_notations(&document.cards)?,
+            },
+            opening: cards_from_notations(&document.opening)?,
+        };
+
+        deck_file.check_opening()?;
+        deck_file.check_details()?;
+        Ok(deck_file)
+    }
+}
+
+// The kinds of file a side deck can be read from and written to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeckFormat {
+    Pzk,
+    Json,
+    Toml,
+}
+
+impl DeckFormat {
+    // Picks the format from the file extension
+    pub fn from_path(path: impl AsRef<Path>) -> Result<DeckFormat, DeckError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+
+        match extension
+            .map(|extension| extension.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("pzk") => Ok(DeckFormat::Pzk),
+            Some("json") => Ok(DeckFormat::Json),
+            Some("toml") => Ok(DeckFormat::Toml),
+            _ => Err(DeckError::UnknownFormat(path.display().to_string())),
+        }
+    }
+
+    pub fn parse(&self, content: &str) -> Result<DeckFile, DeckError> {
+        match self {
+            DeckFormat::Pzk => parse_deck(content),
+            DeckFormat::Json => {
+                serde_json::from_str(content).map_err(|error| DeckError::Format(error.to_string()))
+            }
+            DeckFormat::Toml => {
+                toml::from_str(content).map_err(|error| DeckError::Format(error.to_string()))
+            }
+        }
+    }
+
+    pub fn write(&self, deck_file: &DeckFile) -> Result<String, DeckError> {
+        match self {
+            DeckFormat::Pzk => {
+                deck_file.check_details()?;
+                Ok(deck_file.to_string())
+            }
+            DeckFormat::Json => serde_json::to_string_pretty(deck_file)
+                .map_err(|error| DeckError::Format(error.to_string())),
+            DeckFormat::Toml => {
+                toml::to_string(deck_file).map_err(|error| DeckError::Format(error.to_string()))
+            }
+        }
+    }
+}
+
+// Reads a side deck in whichever format its extension names, .pzk if it has none
+// that is known, and checks it against the given rules
+pub fn read_deck_file(path: impl AsRef<Path>, rules: &DeckRules) -> Result<DeckFile, DeckError> {
+    let format = DeckFormat::from_path(&path).unwrap_or(DeckFormat::Pzk);
+    let file_content = fs::read_to_string(path)?;
+    let deck_file = format.parse(&file_content)?;
+
+    rules.validate(&deck_file.deck)?;
+    Ok(deck_file)
+}
+
+// The part of a .pzk file that card lines are added to
+enum Section {
+    Cards,
+    Opening,
+}
+
+// A card line, either a single card such as "+3" or a quantity such as "2x +3"
+fn parse_card_line(line: &str) -> Option<(usize, Card)> {
+    let (count, card) = match line.split_once(char::is_whitespace) {
+        Some((quantity, card)) => (quantity.strip_suffix('x')?.parse().ok()?, card.trim()),
+        None => (1, line),
+    };
+
+    // Checked before the copies are made, so "99999999x +1" can't exhaust memory
+    if count == 0 || count > usize::from(MAX_CARD_COUNT) {
+        return None;
+    }
+
+    Some((count, Card::from_string(card)?))
+}
+
+// Parses a side deck in either version of the .pzk format, without checking it against any rules.
+//
+// Version 2 files start with a "pazaak-deck 2" line, followed by optional "name:" and
+// "author:" lines, a "[cards]" section and an optional "[opening]" section. Card lines are
+// a card or a quantity such as "2x +3". Anything after a '#' is a comment and blank lines
+// are skipped. Version 1 files are just card lines.
+pub fn parse_deck(content: &str) -> Result<DeckFile, DeckError> {
+    let mut deck_file = DeckFile::default();
+    let mut section = Section::Cards;
+    let mut first_line = true;
+
+    for (i, text) in content.lines().enumerate() {
+        let unparsable = || DeckError::UnparsableLine {
+            line_no: i + 1,
+            text: text.to_string(),
+        };
+
+        let line = text.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // The version header may only be the first line that isn't blank or a comment
+        if let Some(version) = line.strip_prefix("pazaak-deck ") {
+            if !first_line {
+                return Err(unparsable());
+            }
+
+            let version = version.trim().parse::<u32>().map_err(|_| unparsable())?;
+            if version == 0 || version > DECK_FORMAT_VERSION {
+                return Err(DeckError::UnsupportedVersion(version));
+            }
+
+            first_line = false;
+            continue;
+        }
+        first_line = false;
+
+        match line {
+            "[cards]" => section = Section::Cards,
+            "[opening]" => section = Section::Opening,
+            _ => {
+                if let Some((key, value)) = line.split_once(':') {
+                    let value = Some(value.trim().to_string());
+                    match key.trim() {
+                        "name" => deck_file.name = value,
+                        "author" => deck_file.author = value,
+                        _ => return Err(unparsable()),
+                    }
+                    continue;
+                }
+
+                // create cards based on the regex form of the card
+                let (count, card) = parse_card_line(line).ok_or_else(unparsable)?;
+                let cards = match section {
+                    Section::Cards => &mut deck_file.deck.cards,
+                    Section::Opening => &mut deck_file.opening,
+                };
+                cards.extend(std::iter::repeat_n(card, count));
+            }
+        }
+    }
+
+    deck_file.check_opening()?;
+    Ok(deck_file)
+}
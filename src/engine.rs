@@ -0,0 +1,512 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{
+    cards::{Board, Card, CardEffect, Match, ShortDeal, SpecialType, Status},
+    view::PlayerView,
+};
+
+// Seat index into `Match::players` and `Game::board`, 0 or 1
+pub type PlayerId = usize;
+
+// An action a player can take once their turn has started
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Stand,
+    EndTurn,
+    // Play the card at `card_index` in the player's hand. `value_index` picks the
+    // entry of the card's `values_list` to play and is ignored for cards without playstyles
+    Play {
+        card_index: usize,
+        value_index: usize,
+    },
+}
+
+// What happened as a result of applying an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    // A card was played and the player's turn continues
+    CardPlayed,
+    TurnEnded,
+    // Also the outcome of a card that reaches 20 under the auto-stand rule
+    Stood,
+    // Also the outcome of a card that leaves a player over 20 with no way back
+    Busted,
+    // The player filled the table and won the set
+    FilledTable,
+}
+
+// Why a set came to an end
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SetOverReason {
+    BothStanding,
+    Busted(PlayerId),
+    FilledTable(PlayerId),
+    // The board deck ran out, so the set is decided on the boards as they are
+    DeckEmpty,
+}
+
+// Where a match is between calls into the engine. Every call checks that it is
+// allowed in the current phase and moves the match on to the next one:
+//
+//   Phase             | Call         | Next phase
+//   ------------------+--------------+-----------------------------------------------
+//   Dealing           | start_set    | Dealing, with the new set's hands dealt and the
+//                     |              | first player next
+//   Dealing           | start_turn   | AwaitingAction(player) after the draw, or
+//                     |              | SetOver if it filled the table or the board
+//                     |              | deck ran out. Standing players stay in Dealing.
+//                     |              | A draw can also end the turn as Play does.
+//                     |              | Only the `Match::next_player` may start a turn,
+//                     |              | the other player is next after them.
+//   AwaitingAction(p) | Play         | AwaitingAction(p), SetOver(FilledTable(p)), or
+//                     |              | as Stand or EndTurn if it reached 20 or busted
+//   AwaitingAction(p) | Stand        | Dealing, SetOver(BothStanding) or SetOver(Busted(p))
+//   AwaitingAction(p) | EndTurn      | Dealing, or SetOver(Busted(p))
+//   SetOver(_)        | finish_set   | Dealing for the next set, or MatchOver
+//
+// Anything else is an illegal sequence and returns a `RuleError`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GamePhase {
+    // Waiting for the next card to be dealt, either a new set's hands or a turn's draw
+    #[default]
+    Dealing,
+    // A player has drawn and their turn is in progress
+    AwaitingAction(PlayerId),
+    // The set is decided but its point hasn't been awarded yet
+    SetOver(SetOverReason),
+    MatchOver,
+}
+
+impl GamePhase {
+    // The player whose turn is in progress, if any
+    pub fn active_player(&self) -> Option<PlayerId> {
+        match self {
+            GamePhase::AwaitingAction(player) => Some(*player),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleError {
+    NoGameInProgress,
+    InvalidPlayer,
+    NotYourTurn,
+    TurnInProgress,
+    NotPlaying,
+    AlreadyPlayed,
+    InvalidCardIndex,
+    InvalidValueIndex,
+    SetInProgress,
+    SetOver,
+    MatchOver,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::NoGameInProgress => write!(f, "No game is in progress."),
+            RuleError::InvalidPlayer => write!(f, "There is no such player."),
+            RuleError::NotYourTurn => write!(f, "It is not this player's turn."),
+            RuleError::TurnInProgress => write!(f, "Another turn is still in progress."),
+            RuleError::NotPlaying => write!(f, "This player is no longer playing this set."),
+            RuleError::AlreadyPlayed => write!(
+                f,
+                "You have already played this turn, please end your turn or stand."
+            ),
+            RuleError::InvalidCardIndex => write!(f, "There is no card at that index."),
+            RuleError::InvalidValueIndex => write!(f, "That card cannot be played that way."),
+            RuleError::SetInProgress => write!(f, "The current set has not been finished."),
+            RuleError::SetOver => write!(f, "The set is over."),
+            RuleError::MatchOver => write!(f, "The match is over."),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+fn check_player(pazaak_match: &Match, player: PlayerId) -> Result<(), RuleError> {
+    if player >= pazaak_match.players.len() {
+        return Err(RuleError::InvalidPlayer);
+    }
+
+    if pazaak_match.match_detail.round == 0 {
+        return Err(RuleError::NoGameInProgress);
+    }
+
+    Ok(())
+}
+
+// Decides whether the current set is over from the boards, statuses and board deck alone
+fn set_over_reason(pazaak_match: &Match) -> Option<SetOverReason> {
+    let game = pazaak_match.games.last()?;
+    let players = &pazaak_match.players;
+
+    if pazaak_match.rules.fill_the_table {
+        if let Some(player) = game.filled_table() {
+            return Some(SetOverReason::FilledTable(player));
+        }
+    }
+
+    if let Some(player) = (0..2).find(|&player| players[player].status == Status::Busted) {
+        return Some(SetOverReason::Busted(player));
+    }
+
+    if players
+        .iter()
+        .all(|player| player.status == Status::Standing)
+    {
+        return Some(SetOverReason::BothStanding);
+    }
+
+    if game.deck.cards.is_empty() {
+        return Some(SetOverReason::DeckEmpty);
+    }
+
+    None
+}
+
+// Moves the match on once a player's turn is over
+fn end_turn(pazaak_match: &mut Match) {
+    pazaak_match.phase = match set_over_reason(pazaak_match) {
+        Some(reason) => GamePhase::SetOver(reason),
+        None => GamePhase::Dealing,
+    };
+}
+
+// Whether any card in the hand, played any way, would bring the board back to 20 or under
+fn can_rescue(board: &Board, hand: &[Card]) -> bool {
+    card_plays(hand)
+        .into_iter()
+        .any(|action| board_after(board, hand, action).total() <= 20)
+}
+
+// Every card in a hand played every way it can be, in hand order
+pub fn card_plays(hand: &[Card]) -> Vec<Action> {
+    let mut plays = vec![];
+
+    for (card_index, card) in hand.iter().enumerate() {
+        let playstyles = if card.has_playstyles() {
+            card.values_list.len()
+        } else {
+            1
+        };
+
+        plays.extend((0..playstyles).map(|value_index| Action::Play {
+            card_index,
+            value_index,
+        }));
+    }
+
+    plays
+}
+
+// Every action the view's seat may take during its turn: standing, ending the turn, then
+// `card_plays` of its hand unless it has already played a card this turn
+pub fn legal_actions(view: &PlayerView) -> Vec<Action> {
+    let mut actions = vec![Action::Stand, Action::EndTurn];
+    if !view.played_card {
+        actions.extend(card_plays(&view.hand));
+    }
+    actions
+}
+
+// The board once `action` is taken from `hand`, unchanged by actions that play no card.
+// Plays must be in range for the hand, as those from `card_plays` are.
+pub fn board_after(board: &Board, hand: &[Card], action: Action) -> Board {
+    let mut board = board.clone();
+    if let Action::Play {
+        card_index,
+        value_index,
+    } = action
+    {
+        place_card(&mut board, hand[card_index].clone(), value_index);
+    }
+    board
+}
+
+// Applies the bust and auto-stand rules after a draw or a played card has changed the
+// active player's total, ending their turn if either of them applies:
+//
+//   Total | Rule          | Result
+//   ------+---------------+----------------------------------------------------------
+//   > 20  | strict_busts  | Busted once no card can bring it back, either because the
+//         |               | player has played this turn or nothing in their hand helps.
+//         |               | Until then they may play a rescue card or end and bust.
+//   20    | auto_stand    | Standing
+fn check_total(pazaak_match: &mut Match, player: PlayerId) -> Option<Outcome> {
+    let rules = pazaak_match.rules;
+    let game = &pazaak_match.games[pazaak_match.match_detail.round - 1];
+    let board = &game.board[player];
+    let current_player = &mut pazaak_match.players[player];
+
+    let outcome = if board.total() > 20
+        && rules.strict_busts
+        && (game.played_card || !can_rescue(board, &current_player.hand.cards))
+    {
+        current_player.status = Status::Busted;
+        Outcome::Busted
+    } else if board.total() == 20 && rules.auto_stand {
+        current_player.status = Status::Standing;
+        Outcome::Stood
+    } else {
+        return None;
+    };
+
+    end_turn(pazaak_match);
+    Some(outcome)
+}
+
+// Deals the next set. Only allowed before the first set or once the last one is finished.
+pub fn start_set(pazaak_match: &mut Match) -> Result<Vec<ShortDeal>, RuleError> {
+    match pazaak_match.phase {
+        GamePhase::MatchOver => return Err(RuleError::MatchOver),
+        GamePhase::AwaitingAction(_) | GamePhase::SetOver(_) => {
+            return Err(RuleError::SetInProgress)
+        }
+        GamePhase::Dealing => {}
+    }
+
+    // Between turns a set is still being played, after `finish_set` it is decided
+    if pazaak_match.match_detail.round > 0 && set_over_reason(pazaak_match).is_none() {
+        return Err(RuleError::SetInProgress);
+    }
+
+    let short_deals = pazaak_match.new_game();
+    pazaak_match.phase = GamePhase::Dealing;
+    pazaak_match.next_player = 0;
+    Ok(short_deals)
+}
+
+// Starts a player's turn by drawing a card from the board deck onto their board. Players
+// take turns in seat order, so only `Match::next_player` may call this.
+// Returns the drawn card, or None if nothing is drawn: a standing player's turn is skipped,
+// and an empty board deck ends the set. The draw ends the turn straight away if it fills
+// the table, busts or reaches 20. An error leaves the match as it was.
+pub fn start_turn(pazaak_match: &mut Match, player: PlayerId) -> Result<Option<Card>, RuleError> {
+    check_player(pazaak_match, player)?;
+
+    match pazaak_match.phase {
+        GamePhase::MatchOver => return Err(RuleError::MatchOver),
+        GamePhase::SetOver(_) => return Err(RuleError::SetOver),
+        GamePhase::AwaitingAction(_) => return Err(RuleError::TurnInProgress),
+        GamePhase::Dealing => {}
+    }
+
+    // A finished set stays on the table until the next one is dealt
+    if set_over_reason(pazaak_match).is_some() {
+        return Err(RuleError::SetOver);
+    }
+
+    if player != pazaak_match.next_player {
+        return Err(RuleError::NotYourTurn);
+    }
+    let standing = match pazaak_match.players[player].status {
+        Status::Standing => true,
+        Status::Busted => return Err(RuleError::NotPlaying),
+        Status::Playing => false,
+    };
+
+    // Every check has passed, so the turn is taken
+    pazaak_match.next_player = 1 - player;
+    if standing {
+        return Ok(None);
+    }
+
+    let game = &mut pazaak_match.games[pazaak_match.match_detail.round - 1];
+
+    let Some(drawn_card) = game.deck.draw() else {
+        pazaak_match.phase = GamePhase::SetOver(SetOverReason::DeckEmpty);
+        return Ok(None);
+    };
+    game.board[player].draw(drawn_card.clone());
+
+    game.played_card = false;
+
+    // Filling the table wins the set outright, so there is nothing left to do this turn
+    if pazaak_match.rules.fill_the_table && game.board[player].is_filled() {
+        pazaak_match.phase = GamePhase::SetOver(SetOverReason::FilledTable(player));
+    } else {
+        pazaak_match.phase = GamePhase::AwaitingAction(player);
+        check_total(pazaak_match, player);
+    }
+
+    Ok(Some(drawn_card))
+}
+
+// Applies an action for the player whose turn is in progress
+pub fn apply_action(
+    pazaak_match: &mut Match,
+    player: PlayerId,
+    action: Action,
+) -> Result<Outcome, RuleError> {
+    check_player(pazaak_match, player)?;
+
+    match pazaak_match.phase {
+        GamePhase::MatchOver => return Err(RuleError::MatchOver),
+        GamePhase::SetOver(_) => return Err(RuleError::SetOver),
+        _ => {}
+    }
+
+    let game = &mut pazaak_match.games[pazaak_match.match_detail.round - 1];
+    let current_player = &mut pazaak_match.players[player];
+
+    if current_player.status != Status::Playing {
+        return Err(RuleError::NotPlaying);
+    }
+
+    if pazaak_match.phase != GamePhase::AwaitingAction(player) {
+        return Err(RuleError::NotYourTurn);
+    }
+
+    let player_board = &mut game.board[player];
+
+    match action {
+        Action::Stand => {
+            // Standing doesn't save a player who is over 20
+            let outcome = if player_board.total() > 20 && pazaak_match.rules.strict_busts {
+                current_player.status = Status::Busted;
+                Outcome::Busted
+            } else {
+                current_player.status = Status::Standing;
+                Outcome::Stood
+            };
+            end_turn(pazaak_match);
+            Ok(outcome)
+        }
+        Action::EndTurn => {
+            let outcome = if player_board.total() > 20 {
+                current_player.status = Status::Busted;
+                Outcome::Busted
+            } else {
+                Outcome::TurnEnded
+            };
+            end_turn(pazaak_match);
+            Ok(outcome)
+        }
+        Action::Play {
+            card_index,
+            value_index,
+        } => {
+            if game.played_card {
+                return Err(RuleError::AlreadyPlayed);
+            }
+
+            let card = current_player
+                .hand
+                .cards
+                .get(card_index)
+                .ok_or(RuleError::InvalidCardIndex)?;
+
+            if card.has_playstyles() && value_index >= card.values_list.len() {
+                return Err(RuleError::InvalidValueIndex);
+            }
+
+            let card = current_player.hand.cards.remove(card_index);
+            place_card(player_board, card, value_index);
+            game.played_card = true;
+
+            if pazaak_match.rules.fill_the_table && player_board.is_filled() {
+                pazaak_match.phase = GamePhase::SetOver(SetOverReason::FilledTable(player));
+                return Ok(Outcome::FilledTable);
+            }
+
+            Ok(check_total(pazaak_match, player).unwrap_or(Outcome::CardPlayed))
+        }
+    }
+}
+
+// Resolves a card from a hand and puts it on the board, applying its board effect.
+// `value_index` must be in range for cards with playstyles.
+pub fn place_card(board: &mut Board, mut card: Card, value_index: usize) {
+    if card.has_playstyles() {
+        card.resolve_value(value_index);
+    }
+
+    apply_effect(board, &mut card);
+
+    board.cards.push(card);
+}
+
+// Applies a card's effect, if it has one, to the board it is about to be played on.
+// This is the only place card effects are decided:
+//
+//   Card   | Effect                                     | Nothing to act on
+//   -------+--------------------------------------------+---------------------------
+//   X&Y    | Flips the sign of every card already on    | Worth 0 and changes nothing
+//          | the board worth +/-X or +/-Y, side cards   |
+//          | included. The card itself is worth 0.      |
+//   D      | Worth the current value of the last card   | Worth 0 on a board holding
+//          | on the board that isn't an X&Y card, so    | no other cards
+//          | that card counts twice. Negative values    |
+//          | double too, and a card flipped by X&Y is   |
+//          | doubled at its flipped value.              |
+//
+// Effects only see the board as it is when the card is played, a D played after
+// an X&Y copies the flipped value, and an X&Y never flips cards played after it.
+pub fn apply_effect(board: &mut Board, played_card: &mut Card) {
+    match &played_card.effect {
+        Some(CardEffect::InvertValues(values)) => {
+            for card in &mut board.cards {
+                if card.value != 0 && values.iter().any(|value| value.abs() == card.value.abs()) {
+                    card.value = -card.value;
+                }
+            }
+        }
+        Some(CardEffect::DoubleLast) => {
+            // Invert cards carry no value of their own, so there is nothing of theirs to double
+            played_card.value = board
+                .cards
+                .iter()
+                .rev()
+                .find(|card| card.special_type != SpecialType::Invert)
+                .map_or(0, |card| card.value);
+        }
+        None => {}
+    }
+}
+
+// Whether a player has won the current set by filling the table, when that rule is on
+pub fn filled_table(pazaak_match: &Match) -> Option<PlayerId> {
+    if !pazaak_match.rules.fill_the_table {
+        return None;
+    }
+
+    pazaak_match.games.last()?.filled_table()
+}
+
+// A set is over once both players are standing, either player has busted or filled
+// the table, or the board deck has run out, until `finish_set` awards its point
+pub fn is_set_over(pazaak_match: &Match) -> bool {
+    matches!(pazaak_match.phase, GamePhase::SetOver(_))
+}
+
+// Records the winner of the current set and awards them a point
+pub fn finish_set(pazaak_match: &mut Match) -> Result<Option<PlayerId>, RuleError> {
+    if pazaak_match.match_detail.round == 0 {
+        return Err(RuleError::NoGameInProgress);
+    }
+
+    match pazaak_match.phase {
+        GamePhase::MatchOver => return Err(RuleError::MatchOver),
+        GamePhase::Dealing | GamePhase::AwaitingAction(_) => return Err(RuleError::SetInProgress),
+        GamePhase::SetOver(_) => {}
+    }
+
+    let rules = pazaak_match.rules;
+    let game = pazaak_match.current_game();
+    let winner = game.check_win(&rules);
+    game.winner = winner;
+
+    if let Some(winner) = winner {
+        pazaak_match.match_detail.score[winner] += 1;
+    }
+
+    pazaak_match.phase = match pazaak_match.check_win() {
+        Some(_) => GamePhase::MatchOver,
+        None => GamePhase::Dealing,
+    };
+
+    Ok(winner)
+}
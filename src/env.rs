@@ -0,0 +1,288 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::fmt;
+
+use crate::{
+    advisor::DeckCounts,
+    agent::Agent,
+    cards::{Card, Match, SpecialType, Status, HAND_SIZE, TABLE_SIZE},
+    deck::DeckFile,
+    engine::{self, Action, GamePhase, PlayerId, RuleError},
+    rules::Rules,
+    simulate::{self, MatchRecord, MAX_SETS},
+    view::PlayerView,
+};
+
+// Most values any card in a hand can be played as, reached by the +/-1/2 card
+pub const MAX_PLAYSTYLES: usize = 4;
+
+// Action ids are 0 for Stand, 1 for End Turn, then one per hand slot and value index:
+// `2 + card_index * MAX_PLAYSTYLES + value_index`
+pub const ACTION_COUNT: usize = 2 + HAND_SIZE * MAX_PLAYSTYLES;
+
+const SPECIAL_TYPES: [SpecialType; 6] = [
+    SpecialType::None,
+    SpecialType::Flip,
+    SpecialType::Invert,
+    SpecialType::Double,
+    SpecialType::TieBreaker,
+    SpecialType::OneOrTwo,
+];
+
+// Features for each side of the table: total, cards on the table, holds a TieBreaker,
+// standing and busted
+const SIDE_FEATURES: usize = 5;
+// Features for each hand slot: held, a one-hot special type, then every value it can
+// be played as
+const SLOT_FEATURES: usize = 1 + SPECIAL_TYPES.len() + MAX_PLAYSTYLES;
+
+// Length of every observation: both sides of the table, the hand, the cards left of each
+// value in the board deck, then the opponent's hand size, whether a card was played this
+// turn, both scores and the set number
+pub const OBSERVATION_SIZE: usize = 2 * SIDE_FEATURES + HAND_SIZE * SLOT_FEATURES + 10 + 5;
+
+// The learner's view of the match, scaled to roughly 0 to 1, laid out as described by
+// `OBSERVATION_SIZE` with its own side first
+pub type Observation = Vec<f32>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvError {
+    // `step` was called before `reset`, or after the match was over
+    NotRunning,
+    // The action id is out of range or masked out in the current position
+    IllegalAction(usize),
+    Rule(RuleError),
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnvError::NotRunning => write!(f, "No match is running, call reset first."),
+            EnvError::IllegalAction(id) => write!(f, "Action {} is not legal here.", id),
+            EnvError::Rule(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+impl From<RuleError> for EnvError {
+    fn from(error: RuleError) -> Self {
+        EnvError::Rule(error)
+    }
+}
+
+// The action an id stands for, if it is in range
+pub fn action_from_id(id: usize) -> Option<Action> {
+    match id {
+        0 => Some(Action::Stand),
+        1 => Some(Action::EndTurn),
+        _ if id < ACTION_COUNT => Some(Action::Play {
+            card_index: (id - 2) / MAX_PLAYSTYLES,
+            value_index: (id - 2) % MAX_PLAYSTYLES,
+        }),
+        _ => None,
+    }
+}
+
+// The id of an action, if it fits in the action space
+pub fn action_id(action: Action) -> Option<usize> {
+    match action {
+        Action::Stand => Some(0),
+        Action::EndTurn => Some(1),
+        Action::Play {
+            card_index,
+            value_index,
+        } if card_index < HAND_SIZE && value_index < MAX_PLAYSTYLES => {
+            Some(2 + card_index * MAX_PLAYSTYLES + value_index)
+        }
+        Action::Play { .. } => None,
+    }
+}
+
+// A gym-style environment where one seat is played step by step and the other by an
+// agent. Each episode is a whole match, rewarded 1 for winning it, -1 for losing it and
+// 0 for every other step, including matches still undecided after `MAX_SETS` sets.
+pub struct PazaakEnv {
+    pub decks: [DeckFile; 2],
+    pub rules: Rules,
+    // The seat played through `step`, the other is played by `opponent`
+    pub seat: PlayerId,
+    pub opponent: Box<dyn Agent>,
+    pazaak_match: Option<Match>,
+    record: MatchRecord,
+}
+
+impl PazaakEnv {
+    pub fn new(decks: [DeckFile; 2], opponent: Box<dyn Agent>) -> PazaakEnv {
+        PazaakEnv {
+            decks,
+            rules: Rules::default(),
+            seat: 0,
+            opponent,
+            pazaak_match: None,
+            record: MatchRecord::default(),
+        }
+    }
+
+    // Starts a new match, shuffled the same way for the same seed as `simulate` would,
+    // and plays on until the learner's first decision. The opponent is reseeded from
+    // `seed` too, so the same seed always plays out the same episode.
+    pub fn reset(&mut self, seed: u64) -> Result<Observation, EnvError> {
+        if self.seat > 1 {
+            return Err(RuleError::InvalidPlayer.into());
+        }
+
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        let deck1 = self.decks[0].shuffled(&mut rng);
+        let deck2 = self.decks[1].shuffled(&mut rng);
+
+        let mut pazaak_match = Match::new(deck1, deck2, rng);
+        pazaak_match.rules = self.rules;
+        self.pazaak_match = Some(pazaak_match);
+        self.record = MatchRecord::default();
+        self.opponent.reseed(seed);
+
+        self.advance()?;
+        Ok(self.observation())
+    }
+
+    // Takes an action for the learner, then plays on until its next decision or the end
+    // of the match. Returns the observation, the reward and whether the match is over.
+    pub fn step(&mut self, action_id: usize) -> Result<(Observation, f32, bool), EnvError> {
+        if self.is_done() {
+            return Err(EnvError::NotRunning);
+        }
+        if !self
+            .legal_actions()
+            .get(action_id)
+            .copied()
+            .unwrap_or(false)
+        {
+            return Err(EnvError::IllegalAction(action_id));
+        }
+
+        let action = action_from_id(action_id).ok_or(EnvError::IllegalAction(action_id))?;
+        let pazaak_match = self.pazaak_match.as_mut().ok_or(EnvError::NotRunning)?;
+        engine::apply_action(pazaak_match, self.seat, action)?;
+
+        self.advance()?;
+        let done = self.is_done();
+        let reward = if done { self.reward() } else { 0.0 };
+        Ok((self.observation(), reward, done))
+    }
+
+    // Which action ids are legal for the learner right now, indexed by id. All false
+    // once the match is over.
+    pub fn legal_actions(&self) -> Vec<bool> {
+        let mut mask = vec![false; ACTION_COUNT];
+        let Some(pazaak_match) = &self.pazaak_match else {
+            return mask;
+        };
+        if pazaak_match.phase != GamePhase::AwaitingAction(self.seat) {
+            return mask;
+        }
+
+        let view = PlayerView::new(pazaak_match, self.seat);
+        for id in engine::legal_actions(&view)
+            .into_iter()
+            .filter_map(action_id)
+        {
+            mask[id] = true;
+        }
+
+        mask
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pazaak_match
+            .as_ref()
+            .is_none_or(|pazaak_match| pazaak_match.phase == GamePhase::MatchOver)
+    }
+
+    // The match being played, if `reset` has been called
+    pub fn pazaak_match(&self) -> Option<&Match> {
+        self.pazaak_match.as_ref()
+    }
+
+    fn reward(&mut self) -> f32 {
+        match self.pazaak_match.as_mut().and_then(Match::check_win) {
+            Some(winner) if winner == self.seat => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    }
+
+    // Deals sets, draws and plays the opponent's turns until the learner has to act
+    fn advance(&mut self) -> Result<(), EnvError> {
+        let Some(pazaak_match) = self.pazaak_match.as_mut() else {
+            return Err(EnvError::NotRunning);
+        };
+
+        let mut agents: [Option<&dyn Agent>; 2] = [None, None];
+        agents[1 - self.seat] = Some(self.opponent.as_ref());
+        simulate::advance(pazaak_match, agents, &mut self.record)?;
+        Ok(())
+    }
+
+    fn observation(&self) -> Observation {
+        let mut observation = Vec::with_capacity(OBSERVATION_SIZE);
+        let Some(pazaak_match) = &self.pazaak_match else {
+            observation.resize(OBSERVATION_SIZE, 0.0);
+            return observation;
+        };
+        let view = PlayerView::new(pazaak_match, self.seat);
+
+        for (board, status) in [
+            (&view.board, view.status),
+            (&view.opponent_board, view.opponent_status),
+        ] {
+            observation.extend([
+                f32::from(board.total()) / 20.0,
+                board.cards.len() as f32 / TABLE_SIZE as f32,
+                flag(board.has_tiebreaker()),
+                flag(status == Status::Standing),
+                flag(status == Status::Busted),
+            ]);
+        }
+
+        for slot in 0..HAND_SIZE {
+            push_slot(&mut observation, view.hand.get(slot));
+        }
+
+        let deck = DeckCounts::from_view(&view);
+        observation.extend(deck.0.iter().map(|&count| f32::from(count) / 4.0));
+
+        observation.extend([
+            view.opponent_hand_size as f32 / HAND_SIZE as f32,
+            flag(view.played_card),
+            f32::from(view.score[view.seat]) / 3.0,
+            f32::from(view.score[view.opponent()]) / 3.0,
+            view.round as f32 / MAX_SETS as f32,
+        ]);
+
+        observation
+    }
+}
+
+fn flag(value: bool) -> f32 {
+    f32::from(u8::from(value))
+}
+
+fn push_slot(observation: &mut Observation, card: Option<&Card>) {
+    let Some(card) = card else {
+        observation.extend([0.0; SLOT_FEATURES]);
+        return;
+    };
+
+    observation.push(1.0);
+    observation.extend(
+        SPECIAL_TYPES
+            .iter()
+            .map(|&special_type| flag(card.special_type == special_type)),
+    );
+    for value_index in 0..MAX_PLAYSTYLES {
+        let value = card.values_list.get(value_index).copied().unwrap_or(0);
+        observation.push(f32::from(value) / 10.0);
+    }
+}
@@ -0,0 +1,25 @@
+pub mod advisor;
+pub mod agent;
+pub mod cards;
+pub mod deck;
+pub mod engine;
+pub mod env;
+pub mod net;
+pub mod ratings;
+pub mod replay;
+pub mod rules;
+pub mod save;
+pub mod simulate;
+pub mod solver;
+pub mod tournament;
+pub mod util;
+pub mod view;
+
+pub use agent::Agent;
+pub use cards::{
+    Board, Card, CardEffect, Deck, Game, Hand, Match, MatchDetails, Player, SpecialType, Status,
+};
+pub use deck::{DeckError, DeckFile, DeckFormat, DeckRules};
+pub use engine::{apply_action, Action, GamePhase, Outcome, PlayerId, RuleError, SetOverReason};
+pub use rules::{HandPolicy, Rules};
+pub use view::PlayerView;
@@ -0,0 +1,240 @@
+use core::time;
+use crossterm::style::Stylize;
+use pazaak::{
+    advisor, util::parse_option_number, Action, Card, MatchDetails, PlayerView, RuleError,
+};
+use std::{
+    io::{self, Write},
+    thread,
+};
+
+use crate::{
+    messages::{
+        self, get_event_message, hint_label, print_event_log, print_log, print_options, Event,
+    },
+    player_number_to_identifier,
+    ui::{Choice, Ui},
+    DEFAULT_SAVE_PATH,
+};
+
+// The line-based interface that prints a new block of text every turn
+pub struct LineUi {
+    // Whether to show the advisor's odds for every play when picking a card
+    pub hints: bool,
+}
+
+// What the player typed at the action prompt
+enum Input {
+    Stand,
+    Play,
+    EndTurn,
+    Save(String),
+}
+
+impl Ui for LineUi {
+    fn log(&mut self, message: &str) {
+        print_log(message);
+    }
+
+    fn log_event(&mut self, player: usize, event: Event) {
+        print_event_log(player, event);
+    }
+
+    fn show_turn(&mut self, view: &PlayerView) {
+        // The score is always shown as "You" first, from the viewing seat's side
+        let match_detail = MatchDetails {
+            round: view.round,
+            score: [view.score[view.seat], view.score[view.opponent()]],
+        };
+
+        println!("{}", "===========================".blue());
+        println!("{}", match_detail);
+    }
+
+    fn show_board(&mut self, _view: &PlayerView) {}
+
+    // Shows the board and asks the player what to do
+    fn take_action(&mut self, view: &PlayerView) -> Choice {
+        println!("{}", view);
+
+        if view.needs_rescue() {
+            print_log(messages::RESCUE_MESSAGE);
+        }
+
+        // Get the player's input
+        match get_input(view.seat) {
+            Input::Play => match take_play_input(view, self.hints) {
+                Some(action) => Choice::Act(action),
+                None => Choice::Nothing,
+            },
+            Input::Stand => {
+                print_log(&get_event_message(view.seat, Event::Stand));
+                Choice::Act(Action::Stand)
+            }
+            Input::EndTurn => {
+                print_log(&get_event_message(view.seat, Event::EndTurn));
+                Choice::Act(Action::EndTurn)
+            }
+            Input::Save(path) => Choice::Save(path),
+        }
+    }
+
+    fn show_result(&mut self, _view: &PlayerView, message: &str) {
+        println!("{}", message);
+
+        // Wait 2000ms
+        thread::sleep(time::Duration::from_millis(250));
+    }
+
+    fn show_match_end(&mut self, view: &PlayerView, message: &str) {
+        println!("{}", "===========================".blue());
+        println!("{}", view);
+        println!("{}", message);
+    }
+}
+
+// Expecting a string of "stand", "play", "end" or "save" if it isn't one of those then it will return an error
+fn get_input(player: usize) -> Input {
+    println!(
+        "What would you like to do? {}",
+        "(stand, play, end, save [file])".yellow().italic()
+    );
+
+    print!("{}> ", player_number_to_identifier(player));
+    io::stdout().flush().unwrap(); // Ensure the prompt appears immediately
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .expect("Failed to read line");
+
+    match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["stand"] => Input::Stand,
+        ["play"] => Input::Play,
+        ["end"] => Input::EndTurn,
+        ["save"] => Input::Save(DEFAULT_SAVE_PATH.to_string()),
+        ["save", path] => Input::Save(path.to_string()),
+        _ => {
+            print_log(messages::INVALID_INPUT_MESSAGE);
+            get_input(player) // Recursive call for invalid input
+        }
+    }
+}
+
+// Shows the advisor's odds for playing no card and for every way of playing each card
+fn print_hints(view: &PlayerView) {
+    let hints = advisor::advise(view, advisor::DEFAULT_SAMPLES, &mut rand::thread_rng());
+
+    println!("{}", "Hints:".yellow().italic());
+    for hint in &hints {
+        println!(
+            "  {}: {}",
+            hint_label(view, hint),
+            hint.to_string().italic()
+        );
+    }
+}
+
+// Asks the player which card to play and how, returning None if they cancel
+fn take_play_input(view: &PlayerView, hints: bool) -> Option<Action> {
+    if view.played_card {
+        print_log(&RuleError::AlreadyPlayed.to_string());
+        return None;
+    }
+
+    if view.hand.is_empty() {
+        print_log(messages::EMPTY_HAND_MESSAGE);
+        return None;
+    }
+
+    print_log(&get_event_message(view.seat, Event::Play));
+
+    if hints {
+        print_hints(view);
+    }
+
+    let card_index = take_card_input(view.seat, &view.hand)?;
+    let card = &view.hand[card_index];
+
+    let value_index = if card.has_playstyles() {
+        take_playstyle_input(view.seat, card)?
+    } else {
+        0
+    };
+
+    Some(Action::Play {
+        card_index,
+        value_index,
+    })
+}
+
+fn take_card_input(player: usize, hand: &[Card]) -> Option<usize> {
+    let mut input = String::new();
+
+    let input_indicator = format!("(1-{}, cancel)", hand.len());
+
+    println!(
+        "Which card would you like to play? {}",
+        input_indicator.yellow().italic()
+    );
+
+    print_options(hand);
+
+    print!("{}> ", player_number_to_identifier(player));
+    std::io::stdout().flush().unwrap();
+
+    std::io::stdin()
+        .read_line(&mut input)
+        .expect("Failed to read line");
+
+    input = input.trim().to_string();
+
+    if input == "cancel" {
+        return None;
+    }
+
+    match parse_option_number(&input, hand.len()) {
+        Some(card_index) => Some(card_index),
+        None => {
+            print_log(messages::INVALID_INPUT_MESSAGE);
+            take_card_input(player, hand)
+        }
+    }
+}
+
+// Presents the player with the available methods of playing a card and takes their input
+fn take_playstyle_input(player_number: usize, special_card: &Card) -> Option<usize> {
+    let values_count = special_card.values_list.len();
+    if values_count == 0 {
+        return None; // Early return if no options available
+    }
+
+    let input_indicator = format!("(1-{}, cancel)", values_count);
+    println!(
+        "How would you like to play this card? {}",
+        input_indicator.yellow().italic()
+    );
+    print_options(&special_card.values_list);
+
+    print!("{}> ", player_number_to_identifier(player_number));
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .expect("Failed to read line");
+
+    let input = input.trim(); // Trim once and use this trimmed version
+
+    if input.eq_ignore_ascii_case("cancel") {
+        return None;
+    }
+
+    match parse_option_number(input, values_count) {
+        Some(playstyle_index) => Some(playstyle_index),
+        None => {
+            print_log(messages::INVALID_INPUT_MESSAGE);
+            take_playstyle_input(player_number, special_card) // Recursive call for invalid input
+        }
+    }
+}
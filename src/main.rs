@@ -1,22 +1,41 @@
-mod cards;
+mod client;
+mod line;
 mod messages;
-mod util;
-
-use cards::{Match, SpecialType};
-use clap::Parser;
-use core::time;
-use crossterm::style::Stylize;
+mod playback;
+mod session;
+mod tui;
+mod ui;
+
+use clap::{Parser, Subcommand};
+use line::LineUi;
+use messages::print_log;
+use pazaak::net::{self, DEFAULT_PORT};
+use pazaak::ratings::{self, RatedMatch, RatingsStore};
+use pazaak::replay::{Recorder, ReplayEvent, REPLAY_VERSION};
+use pazaak::save;
+use pazaak::simulate::Simulation;
+use pazaak::solver::{self, Position};
+use pazaak::tournament::Tournament;
+use pazaak::{
+    agent, deck, DeckError, DeckFile, DeckFormat, DeckRules, HandPolicy, Match, PlayerId, Rules,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use session::{Seat, Session};
 use std::{
-    collections::{HashMap, HashSet},
     fs,
-    io::{self, Write},
+    io::{self, IsTerminal},
+    net::TcpListener,
+    ops::Range,
     process, thread,
+    time::Duration,
 };
-use util::{get_action_message, print_action_log, print_log, Action};
+use tui::TerminalUi;
+use ui::Ui;
 
-use crate::util::print_options;
+const DEFAULT_SAVE_PATH: &str = "pazaak-save.json";
 
-fn player_number_to_identifier(player: usize) -> &'static str {
+pub fn player_number_to_identifier(player: usize) -> &'static str {
     match player {
         0 => "You",
         1 => "Opponent",
@@ -24,380 +43,768 @@ fn player_number_to_identifier(player: usize) -> &'static str {
     }
 }
 
-// Expecting a string of "draw", "stand", or "play" if it isn't one of those then it will return an error
-fn get_input(player: usize) -> Action {
-    println!(
-        "What would you like to do? {}",
-        "(stand, play, end)".yellow().italic()
-    );
-
-    print!("{}> ", player_number_to_identifier(player));
-    io::stdout().flush().unwrap(); // Ensure the prompt appears immediately
-
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read line");
-
-    match input.trim() {
-        "stand" => Action::Stand,
-        "play" => Action::Play,
-        "end" => Action::EndTurn,
-        _ => {
-            print_log(messages::INVALID_INPUT_MESSAGE);
-            get_input(player) // Recursive call for invalid input
+fn validate_deck_paths(paths: &[String]) -> Result<(), String> {
+    print_log("Validating Deck Paths...");
+    for path in paths {
+        if !std::path::Path::new(path).exists() {
+            return Err(format!(
+                "{} '{}'",
+                messages::INVALID_DECK_PATH_MESSAGE,
+                path
+            ));
         }
+        print_log(&format!("{} '{}'", "Found Deck Path:", path));
     }
+    print_log("Deck Paths Validated!");
+    Ok(())
 }
 
-struct TurnResult {
-    is_finished: bool,
-    played_card: bool,
+fn load_deck(path: &str) -> Result<DeckFile, String> {
+    deck::read_deck_file(path, &DeckRules::kotor())
+        .map_err(|error| format!("{}\nPlease resolve invalid Deck at Path: '{}'", error, path))
 }
 
-fn make_turn(pazaak_match: &mut Match) {
-    for i in 0..2 {
-        print_action_log(i, Action::TurnStart);
+fn load_decks(player_deck_path: &str, opponent_deck_path: &str) -> Result<[DeckFile; 2], String> {
+    validate_deck_paths(&[player_deck_path.to_string(), opponent_deck_path.to_string()])?;
+    Ok([load_deck(player_deck_path)?, load_deck(opponent_deck_path)?])
+}
 
-        // Skip if player is standing
-        if let cards::Status::Standing = pazaak_match.players[i].status {
-            print_action_log(i, Action::Stand);
-            continue;
-        }
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
 
-        // Draw a card to the player's board from the board deck
-        let drawn_card = pazaak_match.current_game().deck.draw().unwrap();
-        pazaak_match.current_game().board[i].cards.push(drawn_card);
+    #[clap(flatten)]
+    play: PlayArgs,
+}
 
-        print_action_log(i, Action::Draw);
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Steps through a recorded match
+    Replay {
+        /// Sets the replay file path
+        #[clap(value_parser)]
+        replay_path: String,
+    },
+    /// Plays many matches between two agents without rendering them and reports the results
+    Simulate {
+        /// Sets the first player's deck file path
+        #[clap(value_parser)]
+        player_deck_path: String,
+
+        /// Sets the second player's deck file path
+        #[clap(value_parser)]
+        opponent_deck_path: String,
+
+        /// The first player's agent, e.g. "ai:basic"
+        #[clap(value_parser)]
+        player_agent: String,
+
+        /// The second player's agent, e.g. "ai:basic"
+        #[clap(value_parser)]
+        opponent_agent: String,
+
+        /// Number of matches to play
+        #[clap(long, default_value_t = 1000)]
+        games: usize,
+
+        /// Seeds every match so a simulation can be repeated exactly
+        #[clap(long)]
+        seed: Option<u64>,
+
+        /// Number of threads to play on, defaults to one per core
+        #[clap(long)]
+        threads: Option<usize>,
+
+        /// Rates every match, with the agents as players
+        #[clap(long)]
+        rated: bool,
+
+        #[clap(flatten)]
+        rules: RuleArgs,
+    },
+    /// Hosts a match over the network, waiting for another player to join
+    Host {
+        /// Sets the host's deck file path
+        #[clap(value_parser)]
+        deck_path: String,
+
+        /// Port to listen for the other player on
+        #[clap(long, default_value_t = DEFAULT_PORT)]
+        port: u16,
+
+        /// Seconds the other player has to pick each action before they are disconnected
+        #[clap(long, default_value_t = 120)]
+        turn_timeout: u64,
+
+        /// Seeds every shuffle so a match can be replayed exactly
+        #[clap(long)]
+        seed: Option<u64>,
+
+        #[clap(flatten)]
+        rules: RuleArgs,
+
+        #[clap(flatten)]
+        ui: UiArgs,
+
+        #[clap(flatten)]
+        profiles: ProfileArgs,
+
+        /// Records every event of the match to a replay file
+        #[clap(long)]
+        record: Option<String>,
+    },
+    /// Works with side deck files
+    Deck {
+        #[clap(subcommand)]
+        command: DeckCommand,
+    },
+    /// Works out the best action in a position described in a TOML file
+    Solve {
+        /// Sets the position file path
+        #[clap(value_parser)]
+        position_path: String,
+
+        #[clap(flatten)]
+        rules: RuleArgs,
+    },
+    /// Shows the ratings leaderboard, or one player's rating history
+    Ratings {
+        /// A profile name or agent id to show the history of
+        #[clap(value_parser)]
+        player: Option<String>,
+    },
+    /// Plays a tournament between decks and agents listed in a TOML config file
+    Tournament {
+        /// Sets the tournament config file path
+        #[clap(value_parser)]
+        config_path: String,
+
+        /// Writes the standings to this CSV file, defaults to "<config>-standings.csv"
+        #[clap(long)]
+        csv: Option<String>,
+
+        /// Starts over instead of resuming the matches saved from an earlier run
+        #[clap(long)]
+        restart: bool,
+    },
+    /// Joins a match hosted with the "host" command
+    Join {
+        /// The host's address, e.g. "localhost:7777"
+        #[clap(value_parser)]
+        address: String,
+
+        /// Sets the joining player's deck file path
+        #[clap(value_parser)]
+        deck_path: String,
+
+        #[clap(flatten)]
+        ui: UiArgs,
+    },
+}
 
-        let mut is_finished = false;
-        let mut played_card = false;
+#[derive(Subcommand, Debug)]
+enum DeckCommand {
+    /// Rewrites a deck file in the canonical form of the current .pzk format, dropping comments
+    Fmt {
+        /// Sets the deck file path
+        #[clap(value_parser)]
+        deck_path: String,
+    },
+    /// Converts a deck between the .pzk, .json and .toml formats, picked by file extension
+    Convert {
+        /// Sets the deck file to read
+        #[clap(value_parser)]
+        input_path: String,
+
+        /// Sets the deck file to write
+        #[clap(value_parser)]
+        output_path: String,
+    },
+}
 
-        while !is_finished {
-            println!("{}", pazaak_match);
+// Optional rules shared by every command that starts matches
+#[derive(clap::Args, Debug)]
+struct RuleArgs {
+    /// Turns off the rule that a player with 9 cards on the table who hasn't busted wins the set
+    #[clap(long)]
+    no_fill_the_table: bool,
 
-            // Get the player's input
-            let action = get_input(i);
-            TurnResult {
-                is_finished,
-                played_card,
-            } = process_action(action, i, pazaak_match, played_card);
-        }
+    /// When hands are dealt: "keep" deals one hand for the whole match, "deal-each-set" deals a fresh hand every set
+    #[clap(long, default_value = "keep")]
+    hand_policy: HandPolicy,
 
-        // Check if the player busted
-        if pazaak_match.players[i].status == cards::Status::Busted {
-            print_log(&format!(
-                "{} {}",
-                player_number_to_identifier(i),
-                messages::BUSTED_MESSAGE
-            ));
+    /// Only busts a player over 20 when they end their turn, instead of as soon as no card can save them or they stand
+    #[clap(long)]
+    no_strict_busts: bool,
+
+    /// Turns off standing automatically when a player's total reaches exactly 20
+    #[clap(long)]
+    no_auto_stand: bool,
+}
+
+impl RuleArgs {
+    fn to_rules(&self) -> Rules {
+        Rules {
+            fill_the_table: !self.no_fill_the_table,
+            hand_policy: self.hand_policy,
+            strict_busts: !self.no_strict_busts,
+            auto_stand: !self.no_auto_stand,
         }
     }
+}
+
+// How the match is shown to the player at the keyboard
+#[derive(clap::Args, Debug)]
+struct UiArgs {
+    /// Uses the line-based interface instead of the full-screen one
+    #[clap(long)]
+    plain: bool,
 
-    // Increment the turn counter
-    pazaak_match.current_game().turn += 1;
+    /// Shows the odds of busting, reaching 20 and winning by standing for every play
+    #[clap(long)]
+    hints: bool,
 }
 
-fn take_card_input(player: usize, hand: &cards::Hand) -> Option<usize> {
-    let mut input = String::new();
+// Names the human players are rated under, agents are rated by their id
+#[derive(clap::Args, Debug)]
+struct ProfileArgs {
+    /// Rates the match for the first player under this profile name
+    #[clap(long, default_value = "player")]
+    profile: String,
 
-    let input_indicator = format!("(0-{}, cancel)", hand.cards.len() - 1);
+    /// Rates the match for a human second player under this profile name
+    #[clap(long, default_value = "opponent")]
+    opponent_profile: String,
+}
 
-    println!(
-        "Which card would you like to play? {}",
-        input_indicator.yellow().italic()
-    );
+#[derive(clap::Args, Debug)]
+struct PlayArgs {
+    /// Sets the player deck file path
+    #[clap(value_parser, required_unless_present = "resume")]
+    player_deck_path: Option<String>,
 
-    print_options(&hand.cards);
+    /// Sets the opponent deck file path
+    #[clap(value_parser, required_unless_present = "resume")]
+    opponent_deck_path: Option<String>,
 
-    print!("{}> ", player_number_to_identifier(player));
-    std::io::stdout().flush().unwrap();
+    /// Who plays the opponent's seat: "human" or a built-in agent such as "ai:basic"
+    #[clap(long, default_value = "human")]
+    opponent: String,
 
-    std::io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read line");
+    /// Seeds every shuffle so a match can be replayed exactly
+    #[clap(long)]
+    seed: Option<u64>,
 
-    input = input.trim().to_string();
+    #[clap(flatten)]
+    rules: RuleArgs,
 
-    if input == "cancel" {
-        return None;
-    }
+    #[clap(flatten)]
+    ui: UiArgs,
 
-    let card_index = input.parse::<usize>().unwrap();
+    #[clap(flatten)]
+    profiles: ProfileArgs,
 
-    if card_index > hand.cards.len() - 1 {
-        print_log(messages::INVALID_INPUT_MESSAGE);
-        take_card_input(player, hand)
-    } else {
-        Some(card_index)
-    }
+    /// Records every event of the match to a replay file
+    #[clap(long, conflicts_with = "resume")]
+    record: Option<String>,
+
+    /// Resumes a match saved with the "save" command
+    #[clap(long)]
+    resume: Option<String>,
+}
+
+fn exit_with_error(error: impl std::fmt::Display) -> ! {
+    eprintln!("{}", error);
+    process::exit(1);
 }
 
-// Presents the player with the available methods of playing a card and takes their input
-fn take_playstyle_input(player_number: usize, special_card: &cards::Card) -> Option<usize> {
-    let values_count = special_card.values_list.len();
-    if values_count == 0 {
-        return None; // Early return if no options available
+fn main() {
+    let args = Args::parse();
+
+    match args.command {
+        Some(Command::Replay { replay_path }) => {
+            playback::play_replay(&replay_path).unwrap_or_else(|error| exit_with_error(error))
+        }
+        Some(Command::Simulate {
+            player_deck_path,
+            opponent_deck_path,
+            player_agent,
+            opponent_agent,
+            games,
+            seed,
+            threads,
+            rated,
+            rules,
+        }) => {
+            let decks = [
+                load_deck(&player_deck_path).unwrap_or_else(|e| exit_with_error(e)),
+                load_deck(&opponent_deck_path).unwrap_or_else(|e| exit_with_error(e)),
+            ];
+            let seed = seed.unwrap_or_else(rand::random);
+            let threads = threads.unwrap_or_else(|| {
+                thread::available_parallelism().map_or(1, |threads| threads.get())
+            });
+
+            let simulation = Simulation {
+                decks,
+                agents: [player_agent, opponent_agent],
+                rules: rules.to_rules(),
+                games,
+                seed,
+                threads,
+            };
+
+            let stats = simulation.run().unwrap_or_else(|name| {
+                exit_with_error(format!("{} '{}'", messages::UNKNOWN_AGENT_MESSAGE, name))
+            });
+            println!(
+                "Simulated '{}' vs '{}' with seed {}",
+                simulation.agents[0], simulation.agents[1], seed
+            );
+            print!("{}", stats);
+
+            if rated {
+                rate_simulation(&simulation, &stats.winners);
+            }
+        }
+        Some(Command::Host {
+            deck_path,
+            port,
+            turn_timeout,
+            seed,
+            rules,
+            ui,
+            profiles,
+            record,
+        }) => host_match(HostArgs {
+            deck_path,
+            port,
+            turn_timeout: Duration::from_secs(turn_timeout),
+            seed,
+            rules: rules.to_rules(),
+            ui,
+            profiles,
+            record,
+        }),
+        Some(Command::Join {
+            address,
+            deck_path,
+            ui,
+        }) => join_match(&address, &deck_path, &ui),
+        Some(Command::Solve {
+            position_path,
+            rules,
+        }) => {
+            solve_position(&position_path, &rules.to_rules()).unwrap_or_else(|e| exit_with_error(e))
+        }
+        Some(Command::Ratings { player }) => {
+            show_ratings(player.as_deref()).unwrap_or_else(|e| exit_with_error(e))
+        }
+        Some(Command::Tournament {
+            config_path,
+            csv,
+            restart,
+        }) => run_tournament(&config_path, csv, restart).unwrap_or_else(|e| exit_with_error(e)),
+        Some(Command::Deck { command }) => match command {
+            DeckCommand::Fmt { deck_path } => format_deck(&deck_path),
+            DeckCommand::Convert {
+                input_path,
+                output_path,
+            } => convert_deck(&input_path, &output_path),
+        }
+        .unwrap_or_else(|error| exit_with_error(error)),
+        None => play_match(args.play),
     }
+}
 
-    let input_indicator = format!("(0-{}, cancel)", values_count - 1);
-    println!(
-        "How would you like to play this card? {}",
-        input_indicator.yellow().italic()
-    );
-    print_options(&special_card.values_list);
+fn format_deck(path: &str) -> Result<(), DeckError> {
+    let deck_file = deck::parse_deck(&fs::read_to_string(path)?)?;
+    fs::write(path, deck_file.to_string())?;
 
-    print!("{}> ", player_number_to_identifier(player_number));
-    io::stdout().flush().unwrap();
+    println!("Formatted '{}'", path);
+    Ok(())
+}
 
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read line");
+fn convert_deck(input_path: &str, output_path: &str) -> Result<(), DeckError> {
+    let input_format = DeckFormat::from_path(input_path)?;
+    let output_format = DeckFormat::from_path(output_path)?;
 
-    let input = input.trim(); // Trim once and use this trimmed version
+    let deck_file = input_format.parse(&fs::read_to_string(input_path)?)?;
+    fs::write(output_path, output_format.write(&deck_file)?)?;
 
-    if input.eq_ignore_ascii_case("cancel") {
-        return None;
-    }
+    println!("Converted '{}' to '{}'", input_path, output_path);
+    Ok(())
+}
 
-    match input.parse::<usize>() {
-        Ok(playstyle_index) if playstyle_index < values_count => Some(playstyle_index),
-        _ => {
-            print_log(messages::INVALID_INPUT_MESSAGE);
-            take_playstyle_input(player_number, special_card) // Recursive call for invalid input
-        }
+fn create_recorder(path: &str) -> Recorder {
+    let file = fs::File::create(path).unwrap_or_else(|error| {
+        exit_with_error(format!(
+            "Unable to create replay file '{}': {}",
+            path, error
+        ))
+    });
+    Recorder::new(Box::new(io::BufWriter::new(file)))
+}
+
+// Shuffles both side decks and starts a match with the given rules
+fn new_match(decks: [DeckFile; 2], seed: u64, rules: Rules) -> Match {
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+
+    // Shuffle each player's deck, keeping their preferred opening on top
+    let player_deck = decks[0].shuffled(&mut rng);
+    let opponent_deck = decks[1].shuffled(&mut rng);
+
+    let mut pzk_match = Match::new(player_deck, opponent_deck, rng);
+    pzk_match.rules = rules;
+    pzk_match
+}
+
+// The full-screen interface needs a real terminal to draw on
+fn create_ui(args: &UiArgs) -> Box<dyn Ui> {
+    if !args.plain && io::stdin().is_terminal() && io::stdout().is_terminal() {
+        Box::new(TerminalUi::new(args.hints).unwrap_or_else(|e| exit_with_error(e)))
+    } else {
+        messages::print_welcome_message();
+        Box::new(LineUi { hints: args.hints })
     }
 }
 
-fn process_action(
-    action: Action,
-    player_number: usize,
-    pazaak_match: &mut Match,
-    already_played: bool,
-) -> TurnResult {
-    let player = &mut pazaak_match.players[player_number];
-    let player_board =
-        &mut pazaak_match.games[pazaak_match.match_detail.round - 1].board[player_number];
-
-    match action {
-        Action::Stand => {
-            print_log(&get_action_message(player_number, action));
-            player.status = cards::Status::Standing;
-        }
-        Action::Play => {
-            if !already_played {
-                print_log(&get_action_message(player_number, action));
-
-                let card_index = take_card_input(player_number, &player.hand);
-
-                match card_index {
-                    Some(card_index) => {
-                        let card = &mut player.hand.cards[card_index];
-
-                        let additional_input_cards: HashSet<SpecialType> =
-                            [SpecialType::Flip, SpecialType::TieBreaker]
-                                .iter()
-                                .cloned()
-                                .collect();
-
-                        if additional_input_cards.contains(&card.special_type) {
-                            let result = take_playstyle_input(player_number, card);
-
-                            match result {
-                                Some(result) => {
-                                    card.resolve_value(result);
-                                }
-                                None => {
-                                    return TurnResult {
-                                        is_finished: false,
-                                        played_card: false,
-                                    };
-                                }
-                            }
-                        }
-
-                        // if the card has a board effect, apply it
-                        if let Some(board_effect) = card.board_effect {
-                            board_effect(player_board, card);
-                        }
-
-                        let card = player.hand.cards.remove(card_index);
-
-                        player_board.cards.push(card);
-
-                        return TurnResult {
-                            is_finished: false,
-                            played_card: true,
-                        };
-                    }
-                    None => {
-                        return TurnResult {
-                            is_finished: false,
-                            played_card: false,
-                        };
-                    }
-                }
-            } else {
-                print_log(messages::ALREADY_PLAYED_MESSAGE);
-                return TurnResult {
-                    is_finished: false,
-                    played_card: false,
-                };
-            }
-        }
-        Action::EndTurn => {
-            print_log(&get_action_message(player_number, action));
-            if player_board.total() > 20 {
-                player.status = cards::Status::Busted;
-            }
+// Logs the seed of a new match and starts its replay file
+fn announce_seed(session: &mut Session, seed: u64) {
+    session.log(&format!("Match Seed: {}", seed));
+    session.record(ReplayEvent::Header {
+        version: REPLAY_VERSION,
+    });
+    session.record(ReplayEvent::Seed(seed));
+}
+
+fn play_match(args: PlayArgs) {
+    let opponent_seat = match args.opponent.as_str() {
+        "human" => Seat::Human,
+        name => Seat::Agent(agent::from_name(name).unwrap_or_else(|| {
+            exit_with_error(format!("{} '{}'", messages::UNKNOWN_OPPONENT_MESSAGE, name))
+        })),
+    };
+
+    let recorder = args.record.as_deref().map(create_recorder);
+
+    let (mut pzk_match, seed) = match &args.resume {
+        Some(path) => {
+            let pzk_match = save::load_match(path).unwrap_or_else(|e| exit_with_error(e));
+            (pzk_match, None)
         }
-        _ => {
-            print_log(&get_action_message(player_number, action));
+        None => {
+            let player_deck_path = args.player_deck_path.expect("required by clap");
+            let opponent_deck_path = args.opponent_deck_path.expect("required by clap");
+
+            let decks = load_decks(&player_deck_path, &opponent_deck_path)
+                .unwrap_or_else(|e| exit_with_error(e));
+
+            let seed = args.seed.unwrap_or_else(rand::random);
+            (new_match(decks, seed, args.rules.to_rules()), Some(seed))
         }
+    };
+
+    let ui = create_ui(&args.ui);
+    let mut session = Session::new([Seat::Human, opponent_seat], recorder, ui);
+
+    match (&args.resume, seed) {
+        (Some(path), _) => session.log(&format!("Resuming Match from '{}'", path)),
+        (None, Some(seed)) => announce_seed(&mut session, seed),
+        (None, None) => {}
     }
 
-    TurnResult {
-        is_finished: true,
-        played_card: false,
+    // Quitting just leaves the match where it is
+    if session.host(&mut pzk_match, args.resume.is_some()).is_ok() {
+        let opponent_name = match args.opponent.as_str() {
+            "human" => &args.profiles.opponent_profile,
+            name => name,
+        };
+        rate_match(&mut pzk_match, [&args.profiles.profile, opponent_name]);
     }
 }
 
-fn validate_deck_paths(paths: &[String]) {
-    print_log("Validating Deck Paths...");
-    for path in paths {
-        if !std::path::Path::new(path).exists() {
-            eprintln!("{} '{}'", messages::INVALID_DECK_PATH_MESSAGE, path);
-            process::exit(1);
-        }
-        print_log(&format!("{} '{}'", "Found Deck Path:", path));
+// Updates the ratings after a finished match
+fn rate_match(pazaak_match: &mut Match, names: [&str; 2]) {
+    let winner = pazaak_match.check_win();
+    match ratings::record_match(names, winner) {
+        Ok(true) => print_log("Ratings updated."),
+        Ok(false) => print_log("Not rated, both seats have the same name."),
+        Err(error) => print_log(&format!("Unable to update ratings: {}", error)),
     }
-    print_log("Deck Paths Validated!");
 }
 
-fn read_deck_file(path: &str) -> cards::Deck {
-    let mut deck = cards::Deck::new();
-
-    let mut card_counts: HashMap<SpecialType, i8> = [
-        (SpecialType::None, 24),
-        (SpecialType::Invert, 12),
-        (SpecialType::Flip, 12),
-        (SpecialType::Double, 1),
-        (SpecialType::TieBreaker, 1),
-    ]
-    .iter()
-    .cloned()
-    .collect();
-
-    let file_content = fs::read_to_string(path)
-        .unwrap_or_else(|_| panic!("Unable to read file at path: {}", path));
-
-    for line in file_content.lines() {
-        // create a card based on the regex form of the card
-        let card = cards::Card::from_string(line).unwrap_or_else(|| {
-            eprintln!("Invalid Card in Deck: '{}'", path);
-            process::exit(1);
-        });
-
-        // Increment the count of the card type
-        let count = card_counts.entry(card.special_type).or_default();
-
-        if *count == 0 {
-            eprintln!("Too many cards of type: '{}'", card.special_type);
-            eprintln!("Please resolve invalid Deck at Path: '{}'", path);
-            process::exit(1);
-        } else {
-            *count -= 1;
-        }
-
-        deck.cards.push(card);
+// Rates the simulated matches as one rating period, with the agents as players. Matches
+// are named by the seed and their index, so rerunning a simulation doesn't rate them again.
+fn rate_simulation(simulation: &Simulation, winners: &[Option<PlayerId>]) {
+    let players = [&*simulation.agents[0], &*simulation.agents[1]];
+    if players[0] == players[1] {
+        print_log("Not rated, both seats have the same name.");
+        return;
     }
 
-    deck
+    let matches: Vec<RatedMatch> = winners
+        .iter()
+        .enumerate()
+        .map(|(index, &winner)| RatedMatch {
+            id: Some(format!(
+                "simulate seed {}, match {}: {} vs {}",
+                simulation.seed, index, players[0], players[1]
+            )),
+            players,
+            winner,
+        })
+        .collect();
+    match ratings::update_store(|store| store.record_period(&matches)) {
+        Ok(rated) => print_log(&format!("{} of {} matches rated.", rated, winners.len())),
+        Err(error) => print_log(&format!("Unable to update ratings: {}", error)),
+    }
 }
 
-#[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
-struct Args {
-    /// Sets the player deck file path
-    #[clap(value_parser)]
-    player_deck_path: String,
-
-    /// Sets the opponent deck file path
-    #[clap(value_parser)]
-    opponent_deck_path: String,
+// Options for hosting a network match, gathered from the "host" command
+struct HostArgs {
+    deck_path: String,
+    port: u16,
+    turn_timeout: Duration,
+    seed: Option<u64>,
+    rules: Rules,
+    ui: UiArgs,
+    profiles: ProfileArgs,
+    record: Option<String>,
 }
 
-fn main() {
-    let args = Args::parse();
+fn host_match(args: HostArgs) {
+    validate_deck_paths(std::slice::from_ref(&args.deck_path))
+        .unwrap_or_else(|e| exit_with_error(e));
+    let host_deck = load_deck(&args.deck_path).unwrap_or_else(|e| exit_with_error(e));
+
+    let listener = TcpListener::bind(("0.0.0.0", args.port)).unwrap_or_else(|error| {
+        exit_with_error(format!("Unable to listen on port {}: {}", args.port, error))
+    });
+    print_log(&format!(
+        "Waiting for a player to join on port {}...",
+        args.port
+    ));
+
+    // Keep waiting through players who can't join, e.g. with an invalid deck
+    let (connection, remote_deck) = loop {
+        match net::accept_player(&listener, &DeckRules::kotor()) {
+            Ok(joined) => break joined,
+            Err(error) => print_log(&format!("A player could not join: {}", error)),
+        }
+    };
+    print_log("A player has joined!");
 
-    let player_deck_path = args.player_deck_path;
-    let opponent_deck_path = args.opponent_deck_path;
+    let recorder = args.record.as_deref().map(create_recorder);
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut pzk_match = new_match([host_deck, remote_deck], seed, args.rules);
 
-    let deck_paths = vec![player_deck_path.to_string(), opponent_deck_path.to_string()];
-    validate_deck_paths(&deck_paths);
-    let mut player_deck = read_deck_file(&player_deck_path);
-    let mut opponent_deck = read_deck_file(&opponent_deck_path);
-    // Shuffle each player's deck
-    player_deck.shuffle();
-    opponent_deck.shuffle();
+    let ui = create_ui(&args.ui);
+    let mut session = Session::new([Seat::Human, Seat::Remote(connection)], recorder, ui);
+    session.turn_timeout = args.turn_timeout;
+    announce_seed(&mut session, seed);
 
-    messages::print_welcome_message();
+    if session.host(&mut pzk_match, false).is_err() {
+        session.close("The match was ended early.");
+        return;
+    }
+    rate_match(
+        &mut pzk_match,
+        [&args.profiles.profile, &args.profiles.opponent_profile],
+    );
+}
 
-    let mut pzk_match = cards::Match::new(player_deck, opponent_deck);
+// Prints every action open in a position with its expected win probability, best first
+fn solve_position(path: &str, rules: &Rules) -> Result<(), String> {
+    let content = fs::read_to_string(path)
+        .map_err(|error| format!("Unable to read position file '{}': {}", path, error))?;
+    let view = Position::parse(&content)
+        .and_then(|position| position.to_view(*rules))
+        .map_err(|error| error.to_string())?;
+
+    println!("{}", view);
+    for (action, win_chance) in solver::solve(&view).actions {
+        println!(
+            "{:<16} {:>5.1}%",
+            messages::action_label(&view, action),
+            win_chance * 100.0
+        );
+    }
+    Ok(())
+}
 
-    // Host Match
-    while pzk_match.check_win().is_none() {
-        pzk_match.new_game();
+// Plays every match of a tournament not already saved, saving its progress after each one
+fn run_tournament(config_path: &str, csv: Option<String>, restart: bool) -> Result<(), String> {
+    let mut tournament = Tournament::load(config_path).map_err(|error| error.to_string())?;
+    let state_path = Tournament::state_path(config_path);
+
+    if !restart && state_path.exists() {
+        tournament
+            .load_state(&state_path)
+            .map_err(|error| format!("{}\nPass --restart to start the tournament over.", error))?;
+        print_log(&format!(
+            "Resuming with {} matches already played",
+            tournament.results.len()
+        ));
+    }
 
-        // Turn Logic
-        loop {
-            println!("{}", "===========================".blue());
-            println!("{}", pzk_match.match_detail);
+    // Matches are named by their config as well, so neither resuming nor restarting rates
+    // a match twice. Each round is rated as one rating period once the next one starts,
+    // or the tournament ends, before the progress that follows it is saved.
+    let config_id = fs::canonicalize(config_path).map_or_else(
+        |_| config_path.to_string(),
+        |path| path.display().to_string(),
+    );
+    let mut rated_rounds = 0;
 
-            make_turn(&mut pzk_match);
+    tournament
+        .run(|tournament, result| {
+            println!("{}", tournament.describe(result));
 
-            // Check if both players are standing
-            if pzk_match
-                .players
-                .iter()
-                .all(|player| player.status == cards::Status::Standing)
-            {
-                break;
+            if result.round > rated_rounds + 1 {
+                rate_rounds(tournament, &config_id, rated_rounds + 1..result.round);
+                rated_rounds = result.round - 1;
             }
+            tournament.save_state(&state_path)
+        })
+        .map_err(|error| error.to_string())?;
+    let rounds = tournament.results.iter().map(|result| result.round).max();
+    rate_rounds(
+        &tournament,
+        &config_id,
+        rated_rounds + 1..rounds.unwrap_or(0) + 1,
+    );
 
-            // Check if a player busted
-            if pzk_match
-                .players
+    let csv_path = csv.unwrap_or_else(|| {
+        let config_path = std::path::Path::new(config_path);
+        let stem = config_path
+            .file_stem()
+            .map_or("tournament".into(), |stem| stem.to_string_lossy());
+        config_path
+            .with_file_name(format!("{}-standings.csv", stem))
+            .to_string_lossy()
+            .into_owned()
+    });
+    fs::write(&csv_path, tournament.standings_csv())
+        .map_err(|error| format!("Unable to write standings to '{}': {}", csv_path, error))?;
+
+    println!();
+    if let Some(name) = &tournament.config.name {
+        println!("{} ({})", name, tournament.config.format);
+    }
+    print!("{}", tournament);
+    print_log(&format!("Standings written to '{}'", csv_path));
+    Ok(())
+}
+
+// Rates each of `rounds` of a tournament as a rating period of its own
+fn rate_rounds(tournament: &Tournament, config_id: &str, rounds: Range<usize>) {
+    if rounds.is_empty() {
+        return;
+    }
+
+    let entrants = &tournament.config.entrants;
+    let rated = ratings::update_store(|store| {
+        for round in rounds {
+            let matches: Vec<RatedMatch> = tournament
+                .results
                 .iter()
-                .any(|player| player.status == cards::Status::Busted)
-            {
-                break;
-            }
+                .filter(|result| result.round == round)
+                .filter_map(|result| {
+                    let away = result.away?;
+                    Some(RatedMatch {
+                        id: Some(format!("{}, {}", config_id, tournament.match_id(result))),
+                        players: [&entrants[result.home].name, &entrants[away].name],
+                        winner: result.winner.map(|winner| usize::from(winner == away)),
+                    })
+                })
+                .collect();
+            store.record_period(&matches);
         }
-        // Post Game Logic
-        let winner = pzk_match.current_game().check_win();
-        match winner {
-            Some(winner) => {
-                println!("{} wins!", player_number_to_identifier(winner));
-                pzk_match.match_detail.score[winner] += 1;
-            }
-            None => println!("Draw!"),
+    });
+    if let Err(error) = rated {
+        print_log(&format!("Unable to update ratings: {}", error));
+    }
+}
+
+// Prints every rated player best first, then their rating history, or just one player's
+fn show_ratings(player: Option<&str>) -> Result<(), String> {
+    let path = RatingsStore::default_path().map_err(|error| error.to_string())?;
+    let store = RatingsStore::load(&path).map_err(|error| error.to_string())?;
+
+    let players = match player {
+        Some(name) => {
+            let ratings = store
+                .players
+                .get_key_value(name)
+                .ok_or_else(|| format!("No ratings for '{}'", name))?;
+            vec![ratings]
         }
+        None => store.leaderboard(),
+    };
+
+    if players.is_empty() {
+        println!("No rated matches yet in '{}'", path.display());
+        return Ok(());
+    }
 
-        // Wait 2000ms
-        thread::sleep(time::Duration::from_millis(250));
+    if player.is_none() {
+        let width = players
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        println!(
+            "{:>4}  {:<width$}  {:>11}  {:>3}  {:>3}  {:>6}",
+            "Rank", "Name", "Rating", "W", "L", "Played"
+        );
+        for (rank, (name, ratings)) in players.iter().enumerate() {
+            println!(
+                "{:>4}  {:<width$}  {:>11}  {:>3}  {:>3}  {:>6}",
+                rank + 1,
+                name,
+                ratings.rating.to_string(),
+                ratings.wins(),
+                ratings.losses(),
+                ratings.history.len()
+            );
+        }
     }
 
-    // Post Match Logic
-    println!("{}", "===========================".blue());
-    println!("{}", pzk_match);
-    let winner = pzk_match.check_win();
-    match winner {
-        Some(winner) => {
-            println!("{} wins!", player_number_to_identifier(winner));
+    for (name, ratings) in players {
+        println!("\n{}:", name);
+        for entry in &ratings.history {
+            let result = match entry.score {
+                1.0 => "won against",
+                0.0 => "lost to",
+                _ => "drew with",
+            };
+            println!(
+                "  #{:<5} {:<11} {:<20} {}",
+                entry.game, result, entry.opponent, entry.rating
+            );
         }
-        None => println!("Draw!"),
+    }
+    Ok(())
+}
+
+fn join_match(address: &str, deck_path: &str, ui_args: &UiArgs) {
+    validate_deck_paths(&[deck_path.to_string()]).unwrap_or_else(|e| exit_with_error(e));
+    let deck = load_deck(deck_path).unwrap_or_else(|e| exit_with_error(e));
+
+    print_log(&format!("Joining '{}'...", address));
+    let (connection, seat) = net::join(address, &deck).unwrap_or_else(|e| exit_with_error(e));
+    print_log(&format!("Joined as Player {}!", seat + 1));
+
+    let mut ui = create_ui(ui_args);
+    let result = client::play_remote(connection, ui.as_mut());
+
+    // Put the terminal back before reporting what went wrong
+    drop(ui);
+    if let Err(error) = result {
+        exit_with_error(error);
     }
 }
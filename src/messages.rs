@@ -1,14 +1,103 @@
+use core::time;
 use crossterm::style::Stylize;
+use pazaak::{advisor::Hint, Action, PlayerView};
+use std::{fmt, thread};
 
 // String templates for messages
 const WELCOME_MESSAGE: &str = "Welcome to pazaak-rs!";
 pub const INVALID_INPUT_MESSAGE: &str = "Invalid input, please try again.";
 pub const INVALID_DECK_PATH_MESSAGE: &str = "Could not find deck file at path:";
-pub const ALREADY_PLAYED_MESSAGE: &str =
-    "You have already played this turn, please end your turn or stand.";
+pub const EMPTY_HAND_MESSAGE: &str = "You have no cards left to play.";
+pub const UNKNOWN_OPPONENT_MESSAGE: &str = "Unknown opponent:";
+pub const UNKNOWN_AGENT_MESSAGE: &str = "Unknown agent:";
 pub const BUSTED_MESSAGE: &str = "has busted!";
+pub const AUTO_STAND_MESSAGE: &str = "reached 20 and stands.";
+pub const RESCUE_MESSAGE: &str =
+    "You are over 20! Play a card to get back under or end your turn to bust.";
+pub const FILLED_TABLE_MESSAGE: &str = "filled the table and wins!";
 
 pub fn print_welcome_message() {
     println!("{}", "===========================".blue().bold());
     println!("{}", WELCOME_MESSAGE.red().italic());
 }
+
+// Events during a turn that get reported to the players
+#[derive(Clone, Copy)]
+pub enum Event {
+    Draw,
+    Stand,
+    EndTurn,
+    Play,
+    TurnStart,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Draw => write!(f, "Draw"),
+            Event::Stand => write!(f, "Stand"),
+            Event::Play => write!(f, "Play"),
+            Event::TurnStart => write!(f, "Turn Start"),
+            Event::EndTurn => write!(f, "End Turn"),
+        }
+    }
+}
+
+pub fn get_event_message(player: usize, event: Event) -> String {
+    let player_str = format!("Player {}", player + 1);
+    match event {
+        Event::Draw => format!("{} Draws...", player_str),
+        Event::Stand => format!("{} Stands...", player_str),
+        Event::Play => format!("{} Plays...", player_str),
+        Event::TurnStart => format!("Starting {}'s Turn...", player_str),
+        Event::EndTurn => format!("Ending {}'s Turn...", player_str),
+    }
+}
+
+// Names an action the view's seat could take, e.g. "Stand", "+3" or "+1/-1T as -1"
+pub fn action_label(view: &PlayerView, action: Action) -> String {
+    match action {
+        Action::Stand => "Stand".to_string(),
+        Action::EndTurn => "End Turn".to_string(),
+        Action::Play {
+            card_index,
+            value_index,
+        } => {
+            let card = &view.hand[card_index];
+            if card.has_playstyles() {
+                format!("{} as {:+}", card.notation(), card.values_list[value_index])
+            } else {
+                card.notation()
+            }
+        }
+    }
+}
+
+// Names the play an advisor hint is about, "No card" or the card played
+pub fn hint_label(view: &PlayerView, hint: &Hint) -> String {
+    hint.action.map_or_else(
+        || "No card".to_string(),
+        |action| action_label(view, action),
+    )
+}
+
+pub fn print_log(message: &str) {
+    println!("{} {}", "~".dark_grey(), message.dark_grey());
+    thread::sleep(time::Duration::from_millis(150));
+}
+
+pub fn print_event_log(player: usize, event: Event) {
+    let message = get_event_message(player, event);
+    print_log(&message);
+    thread::sleep(time::Duration::from_millis(250));
+}
+
+// Show iterable object with indexes
+pub fn print_options<T>(vector: &[T])
+where
+    T: fmt::Display,
+{
+    vector.iter().enumerate().for_each(|(i, object)| {
+        println!("{}: {}", i + 1, object);
+    });
+}
@@ -0,0 +1,301 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::{
+    cards::{Card, Deck},
+    deck::{DeckFile, DeckRules},
+    engine::{Action, PlayerId},
+    view::PlayerView,
+};
+
+// Bumped whenever a message is added, removed or changes shape
+pub const PROTOCOL_VERSION: u32 = 4;
+
+pub const DEFAULT_PORT: u16 = 7777;
+
+// How long either side waits for the other during the handshake
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Longest message either side accepts, newline included. Views are far smaller, so
+// anything longer is a peer that isn't speaking the protocol.
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+// The seat a joining player always takes, the host plays the other one
+pub const REMOTE_SEAT: PlayerId = 1;
+
+// Messages sent from a joining player to the host, one JSON object per line
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    // The first message on a connection. Cards are in the notation read by `Card::from_string`,
+    // `opening` is the preferred opening of the deck.
+    Hello {
+        version: u32,
+        deck: Vec<String>,
+        opening: Vec<String>,
+    },
+    // The answer to a `HostMessage::Prompt`
+    Action(Action),
+    Quit,
+}
+
+// Messages sent from the host to a joining player, one JSON object per line.
+// Views only ever hold what the joining player's own seat is allowed to see.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum HostMessage {
+    Welcome { version: u32, seat: PlayerId },
+    // The host refused the connection, e.g. for an invalid deck
+    Rejected(String),
+    Log(String),
+    Turn(PlayerView),
+    Board(PlayerView),
+    // It is the joining player's turn, answered with a `ClientMessage::Action`
+    Prompt(PlayerView),
+    SetOver { view: PlayerView, message: String },
+    MatchOver { view: PlayerView, message: String },
+    // The match ended early, e.g. because the host quit
+    Closed(String),
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    Disconnected,
+    TimedOut,
+    UnsupportedVersion(u32),
+    Rejected(String),
+    UnexpectedMessage,
+    // A message longer than `MAX_MESSAGE_SIZE`
+    MessageTooLong,
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetError::Io(error) => write!(f, "Network error: {}", error),
+            NetError::Format(error) => write!(f, "Invalid message: {}", error),
+            NetError::Disconnected => write!(f, "The other player disconnected"),
+            NetError::TimedOut => write!(f, "Timed out waiting for the other player"),
+            NetError::UnsupportedVersion(version) => write!(
+                f,
+                "Protocol version {} is not supported, expected {}",
+                version, PROTOCOL_VERSION
+            ),
+            NetError::Rejected(reason) => write!(f, "Rejected by the host: {}", reason),
+            NetError::UnexpectedMessage => write!(f, "Received an unexpected message"),
+            NetError::MessageTooLong => write!(
+                f,
+                "Received a message longer than {} bytes",
+                MAX_MESSAGE_SIZE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NetError::Io(error) => Some(error),
+            NetError::Format(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NetError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => NetError::TimedOut,
+            io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe => NetError::Disconnected,
+            _ => NetError::Io(error),
+        }
+    }
+}
+
+impl From<serde_json::Error> for NetError {
+    fn from(error: serde_json::Error) -> Self {
+        NetError::Format(error)
+    }
+}
+
+// A TCP connection carrying line-delimited JSON messages
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Connection> {
+        let writer = stream.try_clone()?;
+        // Messages are small and a turn waits on every one of them
+        writer.set_nodelay(true)?;
+
+        Ok(Connection {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> Result<(), NetError> {
+        let line = serde_json::to_string(message)?;
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    // Waits for the next message, giving up after `timeout` if there is one
+    pub fn receive<T: DeserializeOwned>(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<T, NetError> {
+        self.reader.get_ref().set_read_timeout(timeout)?;
+
+        // Reads at most one byte past the limit, so a peer that never sends a newline
+        // can't make us buffer without end
+        let mut line = vec![];
+        let limit = MAX_MESSAGE_SIZE as u64 + 1;
+        match (&mut self.reader)
+            .take(limit)
+            .read_until(b'\n', &mut line)?
+        {
+            0 => return Err(NetError::Disconnected),
+            length if length > MAX_MESSAGE_SIZE => return Err(NetError::MessageTooLong),
+            _ => {}
+        }
+
+        Ok(serde_json::from_slice(&line)?)
+    }
+
+    // Sends a last message and waits for the other side to hang up, so that anything
+    // they send in the meantime doesn't reset the connection before they have read it
+    pub fn close<T: Serialize>(mut self, message: &T) -> Result<(), NetError> {
+        self.send(message)?;
+        self.writer.shutdown(Shutdown::Write)?;
+
+        self.reader
+            .get_ref()
+            .set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let mut rest = vec![];
+        self.reader.read_to_end(&mut rest)?;
+        Ok(())
+    }
+}
+
+fn read_remote_cards(cards: &[String]) -> Result<Vec<Card>, String> {
+    cards
+        .iter()
+        .map(|card| Card::from_string(card).ok_or_else(|| format!("Invalid card '{}'", card)))
+        .collect()
+}
+
+// Reads a joining player's deck, checking it against the deck rules
+fn read_remote_deck(
+    cards: &[String],
+    opening: &[String],
+    rules: &DeckRules,
+) -> Result<DeckFile, String> {
+    let deck_file = DeckFile {
+        deck: Deck {
+            cards: read_remote_cards(cards)?,
+        },
+        opening: read_remote_cards(opening)?,
+        ..DeckFile::default()
+    };
+
+    deck_file
+        .check_opening()
+        .and_then(|_| rules.validate(&deck_file.deck))
+        .map_err(|error| error.to_string())?;
+    Ok(deck_file)
+}
+
+// Waits for a player to connect and say hello, returning their connection and side deck.
+// Players with an unsupported protocol version or an invalid deck are told why and dropped.
+pub fn accept_player(
+    listener: &TcpListener,
+    rules: &DeckRules,
+) -> Result<(Connection, DeckFile), NetError> {
+    let (stream, _) = listener.accept()?;
+    let mut connection = Connection::new(stream)?;
+
+    let (version, cards, opening) = match connection.receive(Some(HANDSHAKE_TIMEOUT))? {
+        ClientMessage::Hello {
+            version,
+            deck,
+            opening,
+        } => (version, deck, opening),
+        _ => return Err(NetError::UnexpectedMessage),
+    };
+
+    if version != PROTOCOL_VERSION {
+        let error = NetError::UnsupportedVersion(version);
+        connection.send(&HostMessage::Rejected(error.to_string()))?;
+        return Err(error);
+    }
+
+    let deck = match read_remote_deck(&cards, &opening, rules) {
+        Ok(deck) => deck,
+        Err(reason) => {
+            connection.send(&HostMessage::Rejected(reason.clone()))?;
+            return Err(NetError::Rejected(reason));
+        }
+    };
+
+    connection.send(&HostMessage::Welcome {
+        version: PROTOCOL_VERSION,
+        seat: REMOTE_SEAT,
+    })?;
+
+    Ok((connection, deck))
+}
+
+// Connects to a host and offers it our side deck, returning the connection and our seat
+pub fn join(address: &str, deck_file: &DeckFile) -> Result<(Connection, PlayerId), NetError> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no address to connect to");
+    let mut stream = None;
+
+    for address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, HANDSHAKE_TIMEOUT) {
+            Ok(connected) => {
+                stream = Some(connected);
+                break;
+            }
+            Err(error) => last_error = error,
+        }
+    }
+
+    let stream = stream.ok_or(NetError::Io(last_error))?;
+    let mut connection = Connection::new(stream)?;
+
+    connection.send(&ClientMessage::Hello {
+        version: PROTOCOL_VERSION,
+        deck: deck_file
+            .deck
+            .cards
+            .iter()
+            .map(|card| card.notation())
+            .collect(),
+        opening: deck_file
+            .opening
+            .iter()
+            .map(|card| card.notation())
+            .collect(),
+    })?;
+
+    match connection.receive(Some(HANDSHAKE_TIMEOUT))? {
+        HostMessage::Welcome { version, .. } if version != PROTOCOL_VERSION => {
+            Err(NetError::UnsupportedVersion(version))
+        }
+        HostMessage::Welcome { seat, .. } => Ok((connection, seat)),
+        HostMessage::Rejected(reason) => Err(NetError::Rejected(reason)),
+        _ => Err(NetError::UnexpectedMessage),
+    }
+}
@@ -0,0 +1,104 @@
+use crossterm::style::Stylize;
+use pazaak::{
+    replay::{self, ReplayError, ReplayEvent, Replayer},
+    Status,
+};
+use std::{
+    fs,
+    io::{self, BufReader, Write},
+};
+
+use crate::{
+    messages::{self, get_event_message, print_event_log, print_log, Event},
+    player_number_to_identifier,
+};
+
+// Waits for the viewer to press enter before showing the next step
+fn wait_for_step() {
+    print!("{}", "(enter to step)".yellow().italic());
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .expect("Failed to read line");
+}
+
+// Rebuilds a recorded match event by event, showing the board after every change
+pub fn play_replay(path: &str) -> Result<(), ReplayError> {
+    let file = fs::File::open(path)?;
+    let events = replay::read_replay(BufReader::new(file))?;
+
+    let mut replayer = Replayer::new();
+
+    messages::print_welcome_message();
+
+    for event in events {
+        if !replayer.apply(&event) {
+            print_log(&format!("Skipping event before the first set: {}", event));
+            continue;
+        }
+        let Some(pzk_match) = replayer.pazaak_match.as_ref() else {
+            match event {
+                ReplayEvent::Header { version } => {
+                    print_log(&format!("Replay Version: {}", version))
+                }
+                ReplayEvent::Seed(seed) => print_log(&format!("Match Seed: {}", seed)),
+                _ => {}
+            }
+            continue;
+        };
+
+        match event {
+            ReplayEvent::SetStart { .. } => {
+                println!("{}", "===========================".blue());
+                println!("{}", pzk_match.match_detail);
+            }
+            ReplayEvent::Draw { player, .. } => {
+                print_event_log(player, Event::TurnStart);
+                print_event_log(player, Event::Draw);
+                println!("{}", pzk_match);
+                wait_for_step();
+            }
+            ReplayEvent::Play { player, card, .. } => {
+                print_log(&format!(
+                    "{} {}",
+                    get_event_message(player, Event::Play),
+                    card
+                ));
+                println!("{}", pzk_match);
+                wait_for_step();
+            }
+            ReplayEvent::Stand { player } => {
+                print_log(&get_event_message(player, Event::Stand));
+            }
+            ReplayEvent::EndTurn { player } => {
+                print_log(&get_event_message(player, Event::EndTurn));
+            }
+            ReplayEvent::Status { player, status } => {
+                if status == Status::Busted {
+                    print_log(&format!(
+                        "{} {}",
+                        player_number_to_identifier(player),
+                        messages::BUSTED_MESSAGE
+                    ));
+                }
+            }
+            ReplayEvent::SetOver { winner } => match winner {
+                Some(winner) => println!("{} wins!", player_number_to_identifier(winner)),
+                None => println!("Draw!"),
+            },
+            ReplayEvent::MatchOver { winner } => {
+                println!("{}", "===========================".blue());
+                println!("{}", pzk_match.match_detail);
+                match winner {
+                    Some(winner) => println!("{} wins!", player_number_to_identifier(winner)),
+                    None => println!("Draw!"),
+                }
+            }
+            ReplayEvent::Header { .. } | ReplayEvent::Seed(_) | ReplayEvent::Hand { .. } => {}
+        }
+    }
+
+    Ok(())
+}
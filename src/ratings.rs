@@ -0,0 +1,437 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    f64::consts::PI,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::engine::PlayerId;
+
+// Bumped whenever the layout of the ratings file changes
+pub const RATINGS_VERSION: u32 = 1;
+
+// Overrides where the ratings are kept, e.g. to keep a league's ratings apart
+pub const RATINGS_PATH_ENV: &str = "PAZAAK_RATINGS";
+
+pub const DEFAULT_RATING: f64 = 1500.0;
+pub const DEFAULT_DEVIATION: f64 = 350.0;
+pub const DEFAULT_VOLATILITY: f64 = 0.06;
+
+// Glicko-2's system constant, how much volatility may change after a surprising result
+const TAU: f64 = 0.5;
+// Converts between the Glicko and Glicko-2 rating scales
+const SCALE: f64 = 173.7178;
+const CONVERGENCE: f64 = 0.000001;
+
+// A Glicko-2 rating, kept on the familiar Glicko scale where new players start at 1500
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    pub rating: f64,
+    // How unsure the rating is. About 95% of the time a player's strength is
+    // within two deviations of their rating.
+    pub deviation: f64,
+    // How erratic the player's results are
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating {
+            rating: DEFAULT_RATING,
+            deviation: DEFAULT_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+        }
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0} ± {:.0}", self.rating, self.deviation * 2.0)
+    }
+}
+
+impl Rating {
+    // The rating after one match against `opponent`, treated as a rating period of its
+    // own. `score` is 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn update(&self, opponent: &Rating, score: f64) -> Rating {
+        self.update_period(&[(*opponent, score)])
+    }
+
+    // The rating after a rating period with every opponent's rating and the score against
+    // them, following steps 2 to 8 of Glickman's "Example of the Glicko-2 system". A
+    // period without games only makes the rating less certain.
+    pub fn update_period(&self, results: &[(Rating, f64)]) -> Rating {
+        let mu = (self.rating - DEFAULT_RATING) / SCALE;
+        let phi = self.deviation / SCALE;
+
+        if results.is_empty() {
+            return Rating {
+                deviation: (phi.powi(2) + self.volatility.powi(2)).sqrt() * SCALE,
+                ..*self
+            };
+        }
+
+        // Each game's g(φ) weighting and expected score, and what it adds up to
+        let games: Vec<(f64, f64, f64)> = results
+            .iter()
+            .map(|(opponent, score)| {
+                let opponent_mu = (opponent.rating - DEFAULT_RATING) / SCALE;
+                let opponent_phi = opponent.deviation / SCALE;
+                let g = 1.0 / (1.0 + 3.0 * opponent_phi.powi(2) / PI.powi(2)).sqrt();
+                let expected = 1.0 / (1.0 + (-g * (mu - opponent_mu)).exp());
+                (g, expected, *score)
+            })
+            .collect();
+        let variance = 1.0
+            / games
+                .iter()
+                .map(|(g, expected, _)| g.powi(2) * expected * (1.0 - expected))
+                .sum::<f64>();
+        let improvement: f64 = games
+            .iter()
+            .map(|(g, expected, score)| g * (score - expected))
+            .sum();
+        let delta = variance * improvement;
+
+        let volatility = self.next_volatility(phi, variance, delta);
+        let pre_period_phi = (phi.powi(2) + volatility.powi(2)).sqrt();
+        let new_phi = 1.0 / (1.0 / pre_period_phi.powi(2) + 1.0 / variance).sqrt();
+        let new_mu = mu + new_phi.powi(2) * improvement;
+
+        Rating {
+            rating: new_mu * SCALE + DEFAULT_RATING,
+            deviation: new_phi * SCALE,
+            volatility,
+        }
+    }
+
+    // Solves for the new volatility with the Illinois algorithm, as in step 5 of Glickman's
+    // "Example of the Glicko-2 system"
+    fn next_volatility(&self, phi: f64, variance: f64, delta: f64) -> f64 {
+        let a = self.volatility.powi(2).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            ex * (delta.powi(2) - phi.powi(2) - variance - ex)
+                / (2.0 * (phi.powi(2) + variance + ex).powi(2))
+                - (x - a) / TAU.powi(2)
+        };
+
+        let mut bound_a = a;
+        let mut bound_b = if delta.powi(2) > phi.powi(2) + variance {
+            (delta.powi(2) - phi.powi(2) - variance).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * TAU) < 0.0 {
+                k += 1.0;
+            }
+            a - k * TAU
+        };
+
+        let (mut f_a, mut f_b) = (f(bound_a), f(bound_b));
+        while (bound_b - bound_a).abs() > CONVERGENCE {
+            let next = bound_a + (bound_a - bound_b) * f_a / (f_b - f_a);
+            let f_next = f(next);
+
+            if f_next * f_b <= 0.0 {
+                bound_a = bound_b;
+                f_a = f_b;
+            } else {
+                f_a /= 2.0;
+            }
+            bound_b = next;
+            f_b = f_next;
+        }
+
+        (bound_a / 2.0).exp()
+    }
+}
+
+// A player's rating after one of their matches
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    // Counts every match in the store, so entries from different players line up
+    pub game: usize,
+    pub opponent: String,
+    // 1 for a win, 0.5 for a draw and 0 for a loss
+    pub score: f64,
+    pub rating: Rating,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerRatings {
+    pub rating: Rating,
+    pub history: Vec<HistoryEntry>,
+}
+
+impl PlayerRatings {
+    pub fn wins(&self) -> usize {
+        self.history
+            .iter()
+            .filter(|entry| entry.score == 1.0)
+            .count()
+    }
+
+    pub fn losses(&self) -> usize {
+        self.history
+            .iter()
+            .filter(|entry| entry.score == 0.0)
+            .count()
+    }
+}
+
+#[derive(Debug)]
+pub enum RatingsError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    UnsupportedVersion(u32),
+    // Neither `RATINGS_PATH_ENV` nor the user's data directory could be found
+    NoDataDir,
+}
+
+impl fmt::Display for RatingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RatingsError::Io(error) => write!(f, "Unable to access ratings file: {}", error),
+            RatingsError::Format(error) => write!(f, "Invalid ratings file: {}", error),
+            RatingsError::UnsupportedVersion(version) => write!(
+                f,
+                "Ratings version {} is not supported, expected {}",
+                version, RATINGS_VERSION
+            ),
+            RatingsError::NoDataDir => write!(
+                f,
+                "Unable to find a data directory for the ratings, set {} instead",
+                RATINGS_PATH_ENV
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RatingsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RatingsError::Io(error) => Some(error),
+            RatingsError::Format(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RatingsError {
+    fn from(error: io::Error) -> Self {
+        RatingsError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for RatingsError {
+    fn from(error: serde_json::Error) -> Self {
+        RatingsError::Format(error)
+    }
+}
+
+// A finished match to be rated
+#[derive(Clone, Debug, PartialEq)]
+pub struct RatedMatch<'a> {
+    // Names the match so it is never rated twice, None if it may be
+    pub id: Option<String>,
+    pub players: [&'a str; 2],
+    // Indexed by seat like `Match::check_win`'s winner. None is a draw.
+    pub winner: Option<PlayerId>,
+}
+
+impl RatedMatch<'_> {
+    // 1 for a win, 0.5 for a draw and 0 for a loss
+    fn score(&self, seat: PlayerId) -> f64 {
+        match self.winner {
+            Some(winner) if winner == seat => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+}
+
+// Every rated player, keyed by profile name or agent id
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RatingsStore {
+    version: u32,
+    pub games: usize,
+    pub players: BTreeMap<String, PlayerRatings>,
+    // Ids of every rated match that has one, so replaying them doesn't rate them again
+    #[serde(default)]
+    pub rated: BTreeSet<String>,
+}
+
+impl Default for RatingsStore {
+    fn default() -> Self {
+        RatingsStore {
+            version: RATINGS_VERSION,
+            games: 0,
+            players: BTreeMap::new(),
+            rated: BTreeSet::new(),
+        }
+    }
+}
+
+impl RatingsStore {
+    // `RATINGS_PATH_ENV` if it is set, otherwise "pazaak-rs/ratings.json" in the user's
+    // data directory
+    pub fn default_path() -> Result<PathBuf, RatingsError> {
+        if let Some(path) = std::env::var_os(RATINGS_PATH_ENV) {
+            return Ok(PathBuf::from(path));
+        }
+
+        dirs::data_dir()
+            .map(|directory| directory.join("pazaak-rs").join("ratings.json"))
+            .ok_or(RatingsError::NoDataDir)
+    }
+
+    // An empty store if the file doesn't exist yet
+    pub fn load(path: impl AsRef<Path>) -> Result<RatingsStore, RatingsError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(RatingsStore::default())
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let store: RatingsStore = serde_json::from_str(&content)?;
+        if store.version != RATINGS_VERSION {
+            return Err(RatingsError::UnsupportedVersion(store.version));
+        }
+        Ok(store)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RatingsError> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // Rates `matches` as one rating period: every player's rating is updated once, from
+    // all of their results against their opponents' ratings as they stood before the
+    // period, and every stored player who sat the period out grows less certain. Matches
+    // between two players of the same name, or whose id was rated before, are left out.
+    // Returns how many matches were rated. If none were, nothing changes.
+    pub fn record_period(&mut self, matches: &[RatedMatch]) -> usize {
+        let mut ids = BTreeSet::new();
+        let matches: Vec<&RatedMatch> = matches
+            .iter()
+            .filter(|rated| rated.players[0] != rated.players[1])
+            .filter(|rated| match &rated.id {
+                Some(id) => !self.rated.contains(id) && ids.insert(id),
+                None => true,
+            })
+            .collect();
+        if matches.is_empty() {
+            return 0;
+        }
+
+        let rating = |name: &str| {
+            self.players
+                .get(name)
+                .map_or_else(Rating::default, |player| player.rating)
+        };
+        let mut results: BTreeMap<&str, Vec<(Rating, f64)>> = BTreeMap::new();
+        for rated in &matches {
+            for seat in 0..2 {
+                let opponent = rating(rated.players[1 - seat]);
+                results
+                    .entry(rated.players[seat])
+                    .or_default()
+                    .push((opponent, rated.score(seat)));
+            }
+        }
+        let updated: BTreeMap<&str, Rating> = results
+            .iter()
+            .map(|(&name, results)| (name, rating(name).update_period(results)))
+            .collect();
+
+        for (name, player) in &mut self.players {
+            if !updated.contains_key(name.as_str()) {
+                player.rating = player.rating.update_period(&[]);
+            }
+        }
+
+        // Every match of the period is listed in the history with the rating it ended on
+        for rated in &matches {
+            self.games += 1;
+            for seat in 0..2 {
+                let name = rated.players[seat];
+                let player = self.players.entry(name.to_string()).or_default();
+                player.rating = updated[name];
+                player.history.push(HistoryEntry {
+                    game: self.games,
+                    opponent: rated.players[1 - seat].to_string(),
+                    score: rated.score(seat),
+                    rating: updated[name],
+                });
+            }
+            if let Some(id) = &rated.id {
+                self.rated.insert(id.clone());
+            }
+        }
+
+        matches.len()
+    }
+
+    // Rates a single match as a rating period of its own. Returns false without rating
+    // anything if the same player holds both seats.
+    pub fn record_match(&mut self, players: [&str; 2], winner: Option<PlayerId>) -> bool {
+        self.record_period(&[RatedMatch {
+            id: None,
+            players,
+            winner,
+        }]) == 1
+    }
+
+    // Like `record_match`, but a match whose `id` has been recorded before is left alone
+    pub fn record_match_once(
+        &mut self,
+        id: &str,
+        players: [&str; 2],
+        winner: Option<PlayerId>,
+    ) -> bool {
+        self.record_period(&[RatedMatch {
+            id: Some(id.to_string()),
+            players,
+            winner,
+        }]) == 1
+    }
+
+    // Every player, highest rated first
+    pub fn leaderboard(&self) -> Vec<(&String, &PlayerRatings)> {
+        let mut players: Vec<_> = self.players.iter().collect();
+        players.sort_by(|a, b| b.1.rating.rating.total_cmp(&a.1.rating.rating));
+        players
+    }
+}
+
+// Loads the store at `RatingsStore::default_path`, records a match and saves it again.
+// Returns whether the match was rated, as `RatingsStore::record_match` does.
+pub fn record_match(players: [&str; 2], winner: Option<PlayerId>) -> Result<bool, RatingsError> {
+    update_store(|store| store.record_match(players, winner))
+}
+
+// Records a match with `RatingsStore::record_match_once` in the default store
+pub fn record_match_once(
+    id: &str,
+    players: [&str; 2],
+    winner: Option<PlayerId>,
+) -> Result<bool, RatingsError> {
+    update_store(|store| store.record_match_once(id, players, winner))
+}
+
+// Loads the store at `RatingsStore::default_path`, changes it and saves it again
+pub fn update_store<T>(change: impl FnOnce(&mut RatingsStore) -> T) -> Result<T, RatingsError> {
+    let path = RatingsStore::default_path()?;
+    let mut store = RatingsStore::load(&path)?;
+    let changed = change(&mut store);
+    store.save(&path)?;
+    Ok(changed)
+}
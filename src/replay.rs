@@ -0,0 +1,391 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+    str::FromStr,
+};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+use crate::{
+    cards::{Card, Deck, Hand, Match, MatchDetails, Player, Status},
+    engine::{self, GamePhase, PlayerId},
+    rules::Rules,
+};
+
+// Bumped whenever the meaning of a replay line changes
+pub const REPLAY_VERSION: u32 = 1;
+
+// A single line of a replay file. Cards are written in the notation read by `Card::from_string`.
+#[derive(Clone)]
+pub enum ReplayEvent {
+    Header {
+        version: u32,
+    },
+    Seed(u64),
+    // A player's side-deck hand at the start of every set, after any new hand is dealt
+    Hand {
+        player: PlayerId,
+        cards: Vec<Card>,
+    },
+    SetStart {
+        round: usize,
+    },
+    Draw {
+        player: PlayerId,
+        card: Card,
+    },
+    // `card` is the card as it was in the hand, before its value was resolved
+    Play {
+        player: PlayerId,
+        card_index: usize,
+        value_index: usize,
+        card: Card,
+    },
+    Stand {
+        player: PlayerId,
+    },
+    EndTurn {
+        player: PlayerId,
+    },
+    Status {
+        player: PlayerId,
+        status: Status,
+    },
+    SetOver {
+        winner: Option<PlayerId>,
+    },
+    MatchOver {
+        winner: Option<PlayerId>,
+    },
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    UnparsableLine { line_no: usize, text: String },
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "Unable to read replay: {}", error),
+            ReplayError::UnparsableLine { line_no, text } => {
+                write!(f, "Invalid replay event on line {}: '{}'", line_no, text)
+            }
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "Replay version {} is not supported, expected {}",
+                version, REPLAY_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+fn fmt_winner(winner: Option<PlayerId>) -> String {
+    winner.map_or_else(|| "none".to_string(), |winner| winner.to_string())
+}
+
+impl fmt::Display for ReplayEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayEvent::Header { version } => write!(f, "pazaak-replay {}", version),
+            ReplayEvent::Seed(seed) => write!(f, "seed {}", seed),
+            ReplayEvent::Hand { player, cards } => {
+                write!(f, "hand {}", player)?;
+                for card in cards {
+                    write!(f, " {}", card.notation())?;
+                }
+                Ok(())
+            }
+            ReplayEvent::SetStart { round } => write!(f, "set {}", round),
+            ReplayEvent::Draw { player, card } => {
+                write!(f, "draw {} {}", player, card.notation())
+            }
+            ReplayEvent::Play {
+                player,
+                card_index,
+                value_index,
+                card,
+            } => write!(
+                f,
+                "play {} {} {} {}",
+                player,
+                card_index,
+                value_index,
+                card.notation()
+            ),
+            ReplayEvent::Stand { player } => write!(f, "stand {}", player),
+            ReplayEvent::EndTurn { player } => write!(f, "end {}", player),
+            ReplayEvent::Status { player, status } => {
+                write!(f, "status {} {}", player, status.to_string().to_lowercase())
+            }
+            ReplayEvent::SetOver { winner } => write!(f, "set-over {}", fmt_winner(*winner)),
+            ReplayEvent::MatchOver { winner } => {
+                write!(f, "match-over {}", fmt_winner(*winner))
+            }
+        }
+    }
+}
+
+fn parse_player(word: Option<&str>) -> Option<PlayerId> {
+    word?.parse::<PlayerId>().ok().filter(|player| *player < 2)
+}
+
+fn parse_winner(word: Option<&str>) -> Option<Option<PlayerId>> {
+    match word? {
+        "none" => Some(None),
+        word => parse_player(Some(word)).map(Some),
+    }
+}
+
+fn parse_status(word: Option<&str>) -> Option<Status> {
+    match word? {
+        "playing" => Some(Status::Playing),
+        "standing" => Some(Status::Standing),
+        "busted" => Some(Status::Busted),
+        _ => None,
+    }
+}
+
+impl FromStr for ReplayEvent {
+    type Err = ();
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let kind = words.next().ok_or(())?;
+
+        let event = match kind {
+            "pazaak-replay" => ReplayEvent::Header {
+                version: words.next().and_then(|w| w.parse().ok()).ok_or(())?,
+            },
+            "seed" => ReplayEvent::Seed(words.next().and_then(|w| w.parse().ok()).ok_or(())?),
+            "hand" => ReplayEvent::Hand {
+                player: parse_player(words.next()).ok_or(())?,
+                cards: words
+                    .by_ref()
+                    .map(Card::from_string)
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(())?,
+            },
+            "set" => ReplayEvent::SetStart {
+                round: words.next().and_then(|w| w.parse().ok()).ok_or(())?,
+            },
+            "draw" => ReplayEvent::Draw {
+                player: parse_player(words.next()).ok_or(())?,
+                card: words.next().and_then(Card::from_string).ok_or(())?,
+            },
+            "play" => {
+                let player = parse_player(words.next()).ok_or(())?;
+                let card_index = words.next().and_then(|w| w.parse().ok()).ok_or(())?;
+                let value_index = words.next().and_then(|w| w.parse().ok()).ok_or(())?;
+                let card = words.next().and_then(Card::from_string).ok_or(())?;
+
+                // The card has to have the value it was played as
+                if value_index >= card.values_list.len() {
+                    return Err(());
+                }
+                ReplayEvent::Play {
+                    player,
+                    card_index,
+                    value_index,
+                    card,
+                }
+            }
+            "stand" => ReplayEvent::Stand {
+                player: parse_player(words.next()).ok_or(())?,
+            },
+            "end" => ReplayEvent::EndTurn {
+                player: parse_player(words.next()).ok_or(())?,
+            },
+            "status" => ReplayEvent::Status {
+                player: parse_player(words.next()).ok_or(())?,
+                status: parse_status(words.next()).ok_or(())?,
+            },
+            "set-over" => ReplayEvent::SetOver {
+                winner: parse_winner(words.next()).ok_or(())?,
+            },
+            "match-over" => ReplayEvent::MatchOver {
+                winner: parse_winner(words.next()).ok_or(())?,
+            },
+            _ => return Err(()),
+        };
+
+        // Anything left over means the line was not written by this version
+        match words.next() {
+            Some(_) => Err(()),
+            None => Ok(event),
+        }
+    }
+}
+
+// Writes replay events to a line-delimited replay file as they happen
+pub struct Recorder {
+    writer: Box<dyn Write>,
+}
+
+impl Recorder {
+    pub fn new(writer: Box<dyn Write>) -> Recorder {
+        Recorder { writer }
+    }
+
+    pub fn record(&mut self, event: &ReplayEvent) -> io::Result<()> {
+        writeln!(self.writer, "{}", event)?;
+        self.writer.flush()
+    }
+}
+
+// Reads every event of a replay file, checking its version header
+pub fn read_replay(reader: impl BufRead) -> Result<Vec<ReplayEvent>, ReplayError> {
+    let mut events = vec![];
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let event = line
+            .parse::<ReplayEvent>()
+            .map_err(|_| ReplayError::UnparsableLine {
+                line_no: i + 1,
+                text: line.clone(),
+            })?;
+
+        if let ReplayEvent::Header { version } = event {
+            if version != REPLAY_VERSION {
+                return Err(ReplayError::UnsupportedVersion(version));
+            }
+        }
+
+        events.push(event);
+    }
+
+    Ok(events)
+}
+
+// Rebuilds a recorded match from its events, one at a time
+#[derive(Default)]
+pub struct Replayer {
+    seed: u64,
+    hands: [Vec<Card>; 2],
+    // None until the first set starts
+    pub pazaak_match: Option<Match>,
+}
+
+impl Replayer {
+    pub fn new() -> Replayer {
+        Replayer::default()
+    }
+
+    fn new_match(&self) -> Match {
+        let player = |cards: &Vec<Card>| Player {
+            hand: Hand {
+                cards: cards.clone(),
+            },
+            deck: Deck::new(),
+            status: Status::Playing,
+        };
+
+        Match {
+            games: vec![],
+            players: [player(&self.hands[0]), player(&self.hands[1])],
+            match_detail: MatchDetails::new(),
+            rng: ChaCha12Rng::seed_from_u64(self.seed),
+            rules: Rules::default(),
+            // Replays set the match up event by event rather than through the engine
+            phase: GamePhase::Dealing,
+            next_player: 0,
+        }
+    }
+
+    // Applies an event to the match. Returns false for events that need a set in
+    // progress but come before the first one, which are skipped.
+    pub fn apply(&mut self, event: &ReplayEvent) -> bool {
+        match event {
+            ReplayEvent::Header { .. } => return true,
+            ReplayEvent::Seed(seed) => {
+                self.seed = *seed;
+                return true;
+            }
+            ReplayEvent::Hand { player, cards } => {
+                match self.pazaak_match.as_mut() {
+                    Some(pazaak_match) => pazaak_match.players[*player].hand.cards = cards.clone(),
+                    None => self.hands[*player] = cards.clone(),
+                }
+                return true;
+            }
+            ReplayEvent::SetStart { .. } if self.pazaak_match.is_none() => {
+                self.pazaak_match = Some(self.new_match());
+            }
+            _ => {}
+        }
+
+        let Some(pazaak_match) = self.pazaak_match.as_mut() else {
+            return false;
+        };
+
+        match event {
+            ReplayEvent::SetStart { .. } => {
+                pazaak_match.new_game();
+            }
+            ReplayEvent::Draw { player, card } => {
+                // Take the drawn card out of the board deck so its size stays right
+                let deck = &mut pazaak_match.current_game().deck.cards;
+                if let Some(position) = deck.iter().position(|c| c.value == card.value) {
+                    deck.remove(position);
+                }
+                pazaak_match.current_game().board[*player].draw(card.clone());
+            }
+            ReplayEvent::Play {
+                player,
+                card_index,
+                value_index,
+                card,
+            } => {
+                let hand = &mut pazaak_match.players[*player].hand.cards;
+                if *card_index < hand.len() {
+                    hand.remove(*card_index);
+                }
+                engine::place_card(
+                    &mut pazaak_match.current_game().board[*player],
+                    card.clone(),
+                    *value_index,
+                );
+            }
+            ReplayEvent::Status { player, status } => {
+                pazaak_match.players[*player].status = *status;
+            }
+            ReplayEvent::SetOver { winner } => {
+                pazaak_match.current_game().winner = *winner;
+                if let Some(winner) = winner {
+                    pazaak_match.match_detail.score[*winner] += 1;
+                }
+            }
+            ReplayEvent::Stand { .. }
+            | ReplayEvent::EndTurn { .. }
+            | ReplayEvent::MatchOver { .. }
+            | ReplayEvent::Header { .. }
+            | ReplayEvent::Seed(_)
+            | ReplayEvent::Hand { .. } => {}
+        }
+
+        true
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+// When players are dealt their hand of side-deck cards
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HandPolicy {
+    // KOTOR's rule: one hand is dealt at the start of the match and lasts every set
+    #[default]
+    KeepForMatch,
+    // A fresh hand is dealt from the side deck at the start of every set
+    DealEachSet,
+}
+
+impl fmt::Display for HandPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HandPolicy::KeepForMatch => write!(f, "keep"),
+            HandPolicy::DealEachSet => write!(f, "deal-each-set"),
+        }
+    }
+}
+
+impl FromStr for HandPolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "keep" => Ok(HandPolicy::KeepForMatch),
+            "deal-each-set" => Ok(HandPolicy::DealEachSet),
+            _ => Err(format!(
+                "unknown hand policy '{}', expected 'keep' or 'deal-each-set'",
+                policy
+            )),
+        }
+    }
+}
+
+// House rules that can be switched on or off for a match
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    // A player with a full table of `TABLE_SIZE` cards who hasn't busted wins the set
+    pub fill_the_table: bool,
+    pub hand_policy: HandPolicy,
+    // A player over 20 busts as soon as no card can bring them back, or if they stand,
+    // rather than only when they end their turn
+    pub strict_busts: bool,
+    // A player whose total reaches exactly 20 stands automatically
+    pub auto_stand: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            fill_the_table: true,
+            hand_policy: HandPolicy::default(),
+            strict_busts: true,
+            auto_stand: true,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path};
+
+use crate::cards::Match;
+
+// Bumped whenever the layout of a saved match changes
+pub const SAVE_VERSION: u32 = 5;
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    pazaak_match: Match,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "Unable to access save file: {}", error),
+            SaveError::Format(error) => write!(f, "Invalid save file: {}", error),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "Save version {} is not supported, expected {}",
+                version, SAVE_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io(error) => Some(error),
+            SaveError::Format(error) => Some(error),
+            SaveError::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> Self {
+        SaveError::Format(error)
+    }
+}
+
+// Writes the whole match, including deck order, statuses and the RNG state, to a JSON file
+pub fn save_match(path: impl AsRef<Path>, pazaak_match: &Match) -> Result<(), SaveError> {
+    let save_file = SaveFile {
+        version: SAVE_VERSION,
+        pazaak_match: pazaak_match.clone(),
+    };
+
+    fs::write(path, serde_json::to_string_pretty(&save_file)?)?;
+    Ok(())
+}
+
+pub fn load_match(path: impl AsRef<Path>) -> Result<Match, SaveError> {
+    let save_file: SaveFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+    if save_file.version != SAVE_VERSION {
+        return Err(SaveError::UnsupportedVersion(save_file.version));
+    }
+
+    Ok(save_file.pazaak_match)
+}
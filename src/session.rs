@@ -0,0 +1,429 @@
+use pazaak::net::{ClientMessage, Connection, HostMessage, NetError};
+use pazaak::replay::{Recorder, ReplayEvent};
+use pazaak::{
+    engine, save, Action, Agent, Card, GamePhase, Match, Outcome, PlayerId, PlayerView, Status,
+};
+use std::time::Duration;
+
+use crate::{
+    messages::{self, get_event_message, Event},
+    player_number_to_identifier,
+    ui::{Choice, Ui},
+};
+
+// A player asked to leave the match, or a remote player is gone
+pub struct Quit;
+
+// Who picks the actions for a seat
+pub enum Seat {
+    // Someone at this machine's keyboard, through the session's `Ui`
+    Human,
+    Agent(Box<dyn Agent>),
+    // A player who joined over the network
+    Remote(Connection),
+}
+
+// "You" for the seat being shown to, "Opponent" for the other one
+fn identifier(player: PlayerId, viewer: PlayerId) -> &'static str {
+    player_number_to_identifier(usize::from(player != viewer))
+}
+
+// Everything needed to host a match besides the match itself
+pub struct Session {
+    pub seats: [Seat; 2],
+    pub recorder: Option<Recorder>,
+    pub ui: Box<dyn Ui>,
+    // How long a remote player has to pick each action before they are disconnected
+    pub turn_timeout: Duration,
+    // The seat whose side of the table the local ui shows, the last human to act
+    viewer: PlayerId,
+}
+
+impl Session {
+    pub fn new(seats: [Seat; 2], recorder: Option<Recorder>, ui: Box<dyn Ui>) -> Session {
+        let viewer = seats
+            .iter()
+            .position(|seat| matches!(seat, Seat::Human))
+            .unwrap_or(0);
+
+        Session {
+            seats,
+            recorder,
+            ui,
+            turn_timeout: Duration::from_secs(120),
+            viewer,
+        }
+    }
+
+    // Sends a message to every remote seat, built for that seat. A remote player that has
+    // gone away is only noticed once their action is needed.
+    fn send_remote(&mut self, message: impl Fn(PlayerId) -> HostMessage) {
+        for (seat, controller) in self.seats.iter_mut().enumerate() {
+            if let Seat::Remote(connection) = controller {
+                let _ = connection.send(&message(seat));
+            }
+        }
+    }
+
+    // Shows a message to the local ui and every remote seat, from each one's side of the table
+    fn broadcast(&mut self, message: impl Fn(PlayerId) -> String) {
+        self.ui.log(&message(self.viewer));
+        self.send_remote(|seat| HostMessage::Log(message(seat)));
+    }
+
+    pub fn log(&mut self, message: &str) {
+        self.broadcast(|_| message.to_string());
+    }
+
+    fn log_event(&mut self, player: PlayerId, event: Event) {
+        self.send_remote(|_| HostMessage::Log(get_event_message(player, event)));
+        self.ui.log_event(player, event);
+    }
+
+    fn show_turn(&mut self, pazaak_match: &Match) {
+        self.ui
+            .show_turn(&PlayerView::new(pazaak_match, self.viewer));
+        self.send_remote(|seat| HostMessage::Turn(PlayerView::new(pazaak_match, seat)));
+    }
+
+    fn show_board(&mut self, pazaak_match: &Match) {
+        self.ui
+            .show_board(&PlayerView::new(pazaak_match, self.viewer));
+        self.send_remote(|seat| HostMessage::Board(PlayerView::new(pazaak_match, seat)));
+    }
+
+    // Tells every remote seat the match is over before it was won and hangs up on them
+    pub fn close(&mut self, reason: &str) {
+        for seat in &mut self.seats {
+            if !matches!(seat, Seat::Remote(_)) {
+                continue;
+            }
+
+            // Closing takes the connection, so nobody is left in the seat afterwards
+            if let Seat::Remote(connection) = std::mem::replace(seat, Seat::Human) {
+                let _ = connection.close(&HostMessage::Closed(reason.to_string()));
+            }
+        }
+    }
+
+    // Writes an event to the replay file if the match is being recorded
+    pub fn record(&mut self, event: ReplayEvent) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(error) = recorder.record(&event) {
+                self.ui
+                    .log(&format!("Stopped recording the match: {}", error));
+                self.recorder = None;
+            }
+        }
+    }
+
+    // Records what an applied action did to the match
+    fn record_action(
+        &mut self,
+        player: usize,
+        action: Action,
+        played: Option<Card>,
+        outcome: Outcome,
+    ) {
+        match (action, played) {
+            (
+                Action::Play {
+                    card_index,
+                    value_index,
+                },
+                Some(card),
+            ) => self.record(ReplayEvent::Play {
+                player,
+                card_index,
+                value_index,
+                card,
+            }),
+            (Action::Stand, _) => self.record(ReplayEvent::Stand { player }),
+            _ => self.record(ReplayEvent::EndTurn { player }),
+        }
+
+        let status = match outcome {
+            Outcome::Stood => Status::Standing,
+            Outcome::Busted => Status::Busted,
+            _ => return,
+        };
+        self.record(ReplayEvent::Status { player, status });
+    }
+
+    // Lets an agent pick the action for its seat and reports what it chose
+    fn take_agent_action(&mut self, view: &PlayerView) -> Action {
+        let Seat::Agent(agent) = &self.seats[view.seat] else {
+            panic!("the seat has an agent");
+        };
+        let action = agent.choose_action(view);
+
+        let message = match action {
+            Action::Play { card_index, .. } => match view.hand.get(card_index) {
+                Some(card) => format!("{} {}", get_event_message(view.seat, Event::Play), card),
+                None => get_event_message(view.seat, Event::Play),
+            },
+            Action::Stand => get_event_message(view.seat, Event::Stand),
+            Action::EndTurn => get_event_message(view.seat, Event::EndTurn),
+        };
+        self.log(&message);
+
+        action
+    }
+
+    // Asks the player at the keyboard for an action, saving the match whenever they ask to
+    fn take_human_action(&mut self, pazaak_match: &Match, seat: PlayerId) -> Option<Choice> {
+        // Show the table from the side of whoever is at the keyboard
+        self.viewer = seat;
+
+        match self.ui.take_action(&PlayerView::new(pazaak_match, seat)) {
+            Choice::Save(path) => {
+                match save::save_match(&path, pazaak_match) {
+                    Ok(()) => self.ui.log(&format!("Saved Match to '{}'", path)),
+                    Err(error) => self.ui.log(&error.to_string()),
+                }
+                None
+            }
+            Choice::Nothing => None,
+            choice => Some(choice),
+        }
+    }
+
+    // Sends a remote player their view and waits for their action
+    fn take_remote_action(&mut self, pazaak_match: &Match, seat: PlayerId) -> Result<Action, Quit> {
+        let timeout = self.turn_timeout;
+        let Seat::Remote(connection) = &mut self.seats[seat] else {
+            panic!("the seat is remote");
+        };
+
+        let view = PlayerView::new(pazaak_match, seat);
+        let result = connection
+            .send(&HostMessage::Prompt(view))
+            .and_then(|_| connection.receive(Some(timeout)));
+
+        let reason = match result {
+            Ok(ClientMessage::Action(action)) => return Ok(action),
+            Ok(ClientMessage::Quit) => "left the match".to_string(),
+            Ok(ClientMessage::Hello { .. }) => "sent an unexpected message".to_string(),
+            Err(NetError::TimedOut) => "took too long to play".to_string(),
+            Err(NetError::Disconnected) => "disconnected".to_string(),
+            Err(error) => format!("was dropped ({})", error),
+        };
+
+        // The match can't go on without them, so show the table one last time
+        let message = format!("Player {} {}, the match is over.", seat + 1, reason);
+        self.ui
+            .show_match_end(&PlayerView::new(pazaak_match, self.viewer), &message);
+        Err(Quit)
+    }
+
+    // Takes actions for the player whose turn is in progress until their turn is over
+    fn take_actions(&mut self, pazaak_match: &mut Match, player: PlayerId) -> Result<(), Quit> {
+        loop {
+            // Each seat decides from its own view, never from the whole match
+            let view = PlayerView::new(pazaak_match, player);
+            let is_agent = matches!(self.seats[player], Seat::Agent(_));
+            let action = match self.seats[player] {
+                Seat::Agent(_) => self.take_agent_action(&view),
+                Seat::Remote(_) => self.take_remote_action(pazaak_match, player)?,
+                Seat::Human => match self.take_human_action(pazaak_match, player) {
+                    Some(Choice::Act(action)) => action,
+                    Some(Choice::Quit) => return Err(Quit),
+                    _ => continue,
+                },
+            };
+
+            // Keep the card as it was in the hand for the replay
+            let played = match action {
+                Action::Play { card_index, .. } => pazaak_match.players[player]
+                    .hand
+                    .cards
+                    .get(card_index)
+                    .cloned(),
+                _ => None,
+            };
+
+            let result = engine::apply_action(pazaak_match, player, action);
+            if let Ok(outcome) = result {
+                self.record_action(player, action, played, outcome);
+                self.show_board(pazaak_match);
+            }
+
+            match result {
+                Ok(Outcome::CardPlayed) => {}
+                Ok(_) => return Ok(()),
+                Err(error) => {
+                    self.log(&error.to_string());
+
+                    // An agent that picks an illegal action would otherwise pick it forever
+                    if is_agent {
+                        self.log(&get_event_message(player, Event::EndTurn));
+                        let outcome = engine::apply_action(pazaak_match, player, Action::EndTurn)
+                            .expect("the agent's turn is in progress");
+                        self.record_action(player, Action::EndTurn, None, outcome);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    fn make_turn(&mut self, pazaak_match: &mut Match) -> Result<(), Quit> {
+        // A resumed match may pick up in the middle of a player's turn
+        let resumed_player = pazaak_match.phase.active_player();
+
+        for i in 0..2 {
+            if resumed_player.is_some_and(|player| i < player) {
+                continue;
+            }
+
+            if resumed_player == Some(i) {
+                self.log(&format!("Resuming Player {}'s Turn...", i + 1));
+            } else {
+                self.log_event(i, Event::TurnStart);
+            }
+
+            // Draw a card to the player's board from the board deck, skipping standing players
+            let turn_start = match resumed_player {
+                Some(player) if player == i => Ok(None),
+                _ => engine::start_turn(pazaak_match, i).map(Some),
+            };
+
+            match turn_start {
+                Ok(None) => {}
+                Ok(Some(Some(card))) => {
+                    self.record(ReplayEvent::Draw { player: i, card });
+                    self.show_board(pazaak_match);
+                    self.log_event(i, Event::Draw);
+
+                    // The draw may have busted the player or stood them at 20
+                    let status = pazaak_match.players[i].status;
+                    if status != Status::Playing {
+                        self.record(ReplayEvent::Status { player: i, status });
+                    }
+                }
+                Ok(Some(None)) => {
+                    self.log_event(i, Event::Stand);
+                    continue;
+                }
+                Err(error) => {
+                    self.log(&error.to_string());
+
+                    // Running out of board cards ends the set
+                    if engine::is_set_over(pazaak_match) {
+                        break;
+                    }
+                    continue;
+                }
+            }
+
+            // The draw may have ended the turn by filling the table, busting or reaching 20
+            if pazaak_match.phase == GamePhase::AwaitingAction(i) {
+                self.take_actions(pazaak_match, i)?;
+            }
+
+            // Standing at exactly 20 may have been the engine's doing rather than the player's
+            if pazaak_match.players[i].status == Status::Standing
+                && pazaak_match.rules.auto_stand
+                && pazaak_match.current_game().board[i].total() == 20
+            {
+                self.broadcast(|viewer| {
+                    format!("{} {}", identifier(i, viewer), messages::AUTO_STAND_MESSAGE)
+                });
+            }
+
+            // Check if the player busted
+            if pazaak_match.players[i].status == Status::Busted {
+                self.broadcast(|viewer| {
+                    format!("{} {}", identifier(i, viewer), messages::BUSTED_MESSAGE)
+                });
+            }
+
+            // A bust or a filled table ends the set before the other player's turn
+            if engine::is_set_over(pazaak_match) {
+                break;
+            }
+        }
+
+        // Increment the turn counter
+        pazaak_match.current_game().turn += 1;
+
+        Ok(())
+    }
+
+    // Plays sets until someone wins the match or a player quits
+    pub fn host(&mut self, pazaak_match: &mut Match, mut resuming: bool) -> Result<(), Quit> {
+        while pazaak_match.check_win().is_none() {
+            // A saved match is always in the middle of a set
+            if !resuming {
+                let short_deals =
+                    engine::start_set(pazaak_match).expect("the last set is finished");
+                for short_deal in short_deals {
+                    self.log(&short_deal.to_string());
+                }
+
+                let round = pazaak_match.match_detail.round;
+                self.record(ReplayEvent::SetStart { round });
+                for player in 0..2 {
+                    let cards = pazaak_match.players[player].hand.cards.clone();
+                    self.record(ReplayEvent::Hand { player, cards });
+
+                    let status = Status::Playing;
+                    self.record(ReplayEvent::Status { player, status });
+                }
+            }
+            resuming = false;
+
+            // Turn Logic
+            loop {
+                self.show_turn(pazaak_match);
+
+                self.make_turn(pazaak_match)?;
+
+                // Check if both players are standing or a player busted
+                if engine::is_set_over(pazaak_match) {
+                    break;
+                }
+            }
+
+            // Post Game Logic
+            let winner = engine::finish_set(pazaak_match).expect("a set is in progress");
+            self.record(ReplayEvent::SetOver { winner });
+            let filled_table = engine::filled_table(pazaak_match);
+            let message = |viewer| match winner {
+                Some(winner) if filled_table == Some(winner) => format!(
+                    "{} {}",
+                    identifier(winner, viewer),
+                    messages::FILLED_TABLE_MESSAGE
+                ),
+                Some(winner) => format!("{} wins!", identifier(winner, viewer)),
+                None => "Draw!".to_string(),
+            };
+            self.send_remote(|seat| HostMessage::SetOver {
+                view: PlayerView::new(pazaak_match, seat),
+                message: message(seat),
+            });
+            self.ui.show_result(
+                &PlayerView::new(pazaak_match, self.viewer),
+                &message(self.viewer),
+            );
+        }
+
+        // Post Match Logic
+        let winner = pazaak_match.check_win();
+        self.record(ReplayEvent::MatchOver { winner });
+        let message = |viewer| match winner {
+            Some(winner) => format!("{} wins!", identifier(winner, viewer)),
+            None => "Draw!".to_string(),
+        };
+        self.send_remote(|seat| HostMessage::MatchOver {
+            view: PlayerView::new(pazaak_match, seat),
+            message: message(seat),
+        });
+        self.ui.show_match_end(
+            &PlayerView::new(pazaak_match, self.viewer),
+            &message(self.viewer),
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use std::{collections::BTreeMap, fmt, thread};
+
+use crate::{
+    agent::{self, Agent},
+    cards::{Match, Status},
+    deck::DeckFile,
+    engine::{self, Action, GamePhase, PlayerId, RuleError},
+    rules::{HandPolicy, Rules},
+    view::PlayerView,
+};
+
+// Matches between agents that keep drawing sets are called a draw after this many sets
+pub const MAX_SETS: usize = 50;
+
+// What happened in a single match played without a UI
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchRecord {
+    // None if the match hit `MAX_SETS` without a winner
+    pub winner: Option<PlayerId>,
+    pub score: [u8; 2],
+    // The winner of every set, in order, None for drawn sets
+    pub set_winners: Vec<Option<PlayerId>>,
+    // Number of sets each seat busted in
+    pub busts: [usize; 2],
+    // Notation of every side card each seat was dealt and played, in order
+    pub dealt: [Vec<String>; 2],
+    pub played: [Vec<String>; 2],
+}
+
+// Takes the agent's next action in the turn it is playing
+fn play_action(
+    pazaak_match: &mut Match,
+    player: PlayerId,
+    agent: &dyn Agent,
+    record: &mut MatchRecord,
+) -> Result<(), RuleError> {
+    let view = PlayerView::new(pazaak_match, player);
+    let action = agent.choose_action(&view);
+
+    let played = match action {
+        Action::Play { card_index, .. } => pazaak_match.players[player]
+            .hand
+            .cards
+            .get(card_index)
+            .map(|card| card.notation()),
+        _ => None,
+    };
+
+    match engine::apply_action(pazaak_match, player, action) {
+        Ok(_) => {
+            if let Some(card) = played {
+                record.played[player].push(card);
+            }
+        }
+        // An agent that picks an illegal action would otherwise pick it forever
+        Err(_) => {
+            engine::apply_action(pazaak_match, player, Action::EndTurn)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Deals the next set, noting the hands dealt for it in the record
+fn deal_set(pazaak_match: &mut Match, record: &mut MatchRecord) -> Result<(), RuleError> {
+    engine::start_set(pazaak_match)?;
+
+    // Hands are only dealt at the start of the match unless the policy deals every set
+    let dealt = pazaak_match.match_detail.round == 1
+        || pazaak_match.rules.hand_policy == HandPolicy::DealEachSet;
+    if dealt {
+        for (player, dealt) in record.dealt.iter_mut().enumerate() {
+            let hand = &pazaak_match.players[player].hand.cards;
+            dealt.extend(hand.iter().map(|card| card.notation()));
+        }
+    }
+
+    Ok(())
+}
+
+// Awards the point for a decided set, ending undecided matches after `MAX_SETS` sets
+fn award_set(pazaak_match: &mut Match, record: &mut MatchRecord) -> Result<(), RuleError> {
+    for (player, busts) in record.busts.iter_mut().enumerate() {
+        if pazaak_match.players[player].status == Status::Busted {
+            *busts += 1;
+        }
+    }
+
+    let winner = engine::finish_set(pazaak_match)?;
+    record.set_winners.push(winner);
+
+    if pazaak_match.phase == GamePhase::Dealing && pazaak_match.match_detail.round >= MAX_SETS {
+        pazaak_match.phase = GamePhase::MatchOver;
+    }
+    Ok(())
+}
+
+// Drives a match on from wherever it is: deals sets, draws for every turn in order and
+// plays the turns of seats with an agent, until a seat without one has to act or the
+// match is over. Everything that happens is added to `record`.
+pub fn advance(
+    pazaak_match: &mut Match,
+    agents: [Option<&dyn Agent>; 2],
+    record: &mut MatchRecord,
+) -> Result<(), RuleError> {
+    loop {
+        match pazaak_match.phase {
+            GamePhase::MatchOver => {
+                record.winner = pazaak_match.check_win();
+                record.score = pazaak_match.match_detail.score;
+                return Ok(());
+            }
+            GamePhase::AwaitingAction(player) => match agents[player] {
+                Some(agent) => play_action(pazaak_match, player, agent, record)?,
+                None => return Ok(()),
+            },
+            GamePhase::SetOver(_) => award_set(pazaak_match, record)?,
+            GamePhase::Dealing => {
+                match engine::start_turn(pazaak_match, pazaak_match.next_player) {
+                    // Standing players skip their turn, and an empty board deck ends the set
+                    Ok(_) => {}
+                    // Before the first set or after a finished one, deal the next
+                    Err(RuleError::NoGameInProgress) | Err(RuleError::SetOver) => {
+                        deal_set(pazaak_match, record)?
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+    }
+}
+
+// Plays a whole match between two agents, with no rendering or delays
+pub fn play_match(pazaak_match: &mut Match, agents: [&dyn Agent; 2]) -> MatchRecord {
+    let mut record = MatchRecord::default();
+    // With an agent in every seat the match is only ever moved on in order
+    advance(pazaak_match, agents.map(Some), &mut record)
+        .expect("agents in both seats can always finish a match");
+    record
+}
+
+// How often a side card was dealt and played across every match
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CardUsage {
+    pub dealt: usize,
+    pub played: usize,
+}
+
+// Totals over many simulated matches, indexed by seat where there is one per player
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimulationStats {
+    pub matches: usize,
+    pub wins: [usize; 2],
+    pub draws: usize,
+    pub sets: usize,
+    pub drawn_sets: usize,
+    pub busts: [usize; 2],
+    // Keyed by card notation, sorted so reports come out the same every run
+    pub cards: [BTreeMap<String, CardUsage>; 2],
+    // Every match's winner, in the order the matches were added. None is a draw.
+    pub winners: Vec<Option<PlayerId>>,
+}
+
+impl SimulationStats {
+    pub fn add(&mut self, record: &MatchRecord) {
+        self.matches += 1;
+        match record.winner {
+            Some(winner) => self.wins[winner] += 1,
+            None => self.draws += 1,
+        }
+        self.winners.push(record.winner);
+
+        self.sets += record.set_winners.len();
+        self.drawn_sets += record
+            .set_winners
+            .iter()
+            .filter(|winner| winner.is_none())
+            .count();
+
+        for player in 0..2 {
+            self.busts[player] += record.busts[player];
+
+            for card in &record.dealt[player] {
+                self.cards[player].entry(card.clone()).or_default().dealt += 1;
+            }
+            for card in &record.played[player] {
+                self.cards[player].entry(card.clone()).or_default().played += 1;
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &SimulationStats) {
+        self.matches += other.matches;
+        self.draws += other.draws;
+        self.sets += other.sets;
+        self.drawn_sets += other.drawn_sets;
+        self.winners.extend_from_slice(&other.winners);
+
+        for player in 0..2 {
+            self.wins[player] += other.wins[player];
+            self.busts[player] += other.busts[player];
+
+            for (card, usage) in &other.cards[player] {
+                let total = self.cards[player].entry(card.clone()).or_default();
+                total.dealt += usage.dealt;
+                total.played += usage.played;
+            }
+        }
+    }
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 * 100.0 / total as f64
+    }
+}
+
+impl fmt::Display for SimulationStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Matches: {}", self.matches)?;
+        for player in 0..2 {
+            writeln!(
+                f,
+                "Player {} win rate: {:.1}%",
+                player + 1,
+                percent(self.wins[player], self.matches)
+            )?;
+        }
+        writeln!(
+            f,
+            "Draw rate: {:.1}% of matches, {:.1}% of sets",
+            percent(self.draws, self.matches),
+            percent(self.drawn_sets, self.sets)
+        )?;
+        writeln!(
+            f,
+            "Average sets per match: {:.2}",
+            self.sets as f64 / self.matches.max(1) as f64
+        )?;
+        for player in 0..2 {
+            writeln!(
+                f,
+                "Player {} bust rate: {:.1}% of sets",
+                player + 1,
+                percent(self.busts[player], self.sets)
+            )?;
+        }
+
+        for player in 0..2 {
+            writeln!(f, "Player {} side cards:", player + 1)?;
+            for (card, usage) in &self.cards[player] {
+                writeln!(
+                    f,
+                    "  {:>8}  played {} of {} times dealt ({:.1}%), {:.2} per match",
+                    card,
+                    usage.played,
+                    usage.dealt,
+                    percent(usage.played, usage.dealt),
+                    usage.played as f64 / self.matches.max(1) as f64
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Everything needed to run a batch of matches between two agents
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    pub decks: [DeckFile; 2],
+    // Built-in agent names, as accepted by `agent::from_name`
+    pub agents: [String; 2],
+    pub rules: Rules,
+    pub games: usize,
+    pub seed: u64,
+    pub threads: usize,
+}
+
+impl Simulation {
+    // Match `index` always gets the same shuffles for a given seed, whichever thread plays it
+    // and whatever it played before. Both agents are reseeded for every match, from a
+    // stream of their own so that their choices don't change the shuffles.
+    pub fn play_game(&self, index: usize, mut agents: [&mut dyn Agent; 2]) -> MatchRecord {
+        let mut agent_seeds = ChaCha12Rng::seed_from_u64(self.seed);
+        agent_seeds.set_stream((1 << 63) | index as u64);
+        for agent in &mut agents {
+            agent.reseed(agent_seeds.next_u64());
+        }
+
+        let mut rng = ChaCha12Rng::seed_from_u64(self.seed);
+        rng.set_stream(index as u64);
+
+        let deck1 = self.decks[0].shuffled(&mut rng);
+        let deck2 = self.decks[1].shuffled(&mut rng);
+
+        let mut pazaak_match = Match::new(deck1, deck2, rng);
+        pazaak_match.rules = self.rules;
+
+        play_match(&mut pazaak_match, [&*agents[0], &*agents[1]])
+    }
+
+    // Plays every match, split across `threads` threads. Returns the first agent name
+    // that isn't a built-in agent as the error.
+    pub fn run(&self) -> Result<SimulationStats, String> {
+        for name in &self.agents {
+            if agent::from_name(name).is_none() {
+                return Err(name.clone());
+            }
+        }
+
+        let threads = self.threads.clamp(1, self.games.max(1));
+        // Each worker plays a run of consecutive matches, so merging their stats in order
+        // keeps the winners in match order
+        let chunk = self.games.div_ceil(threads);
+
+        let results = thread::scope(|scope| {
+            let workers = (0..threads)
+                .map(|worker| {
+                    scope.spawn(move || {
+                        // Agents aren't shared between threads, so each worker builds its own
+                        let mut agent1 = agent::from_name(&self.agents[0]).expect("checked above");
+                        let mut agent2 = agent::from_name(&self.agents[1]).expect("checked above");
+
+                        let mut stats = SimulationStats::default();
+                        for index in worker * chunk..((worker + 1) * chunk).min(self.games) {
+                            let record = self.play_game(index, [agent1.as_mut(), agent2.as_mut()]);
+                            stats.add(&record);
+                        }
+                        stats
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("simulation thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        let mut stats = SimulationStats::default();
+        for result in &results {
+            stats.merge(result);
+        }
+        Ok(stats)
+    }
+}
@@ -0,0 +1,588 @@
+use serde::Deserialize;
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    advisor::DeckCounts,
+    agent::Agent,
+    cards::{Board, Card, CardEffect, SpecialType, Status, HAND_SIZE, TABLE_SIZE},
+    engine::{self, Action, PlayerId},
+    rules::{HandPolicy, Rules},
+    view::PlayerView,
+};
+
+// The opponent's hand is hidden, so they are assumed to play no side cards and to stand
+// once they reach this total, or once they catch up with us if we are standing
+pub const OPPONENT_STAND_THRESHOLD: i8 = 17;
+
+// Number of draws ahead the board deck is counted exactly. Further draws are taken from the
+// deck as it was in the position being solved, which barely moves the odds and lets the
+// many ways of reaching the same totals share one entry in the memo.
+pub const EXACT_DRAWS: u8 = 4;
+
+// Win chance given up for each side card played when the hand has to last the match, so
+// a card is only spent in one set when it makes enough of a difference there. It only
+// ranks actions, the win chances reported are the real ones.
+pub const CARD_COST: f64 = 0.1;
+
+// How good a line is: the score it is ranked by, which is `win_chance` less the cost of
+// the side cards it spends, and the chance of winning the set by following it
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Value {
+    score: f64,
+    win_chance: f64,
+}
+
+impl Value {
+    const LOSS: Value = Value::certain(false);
+    const WIN: Value = Value::certain(true);
+
+    const fn certain(won: bool) -> Value {
+        let value = if won { 1.0 } else { 0.0 };
+        Value {
+            score: value,
+            win_chance: value,
+        }
+    }
+}
+
+// One side of the table reduced to what the rest of the set can depend on
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct Side {
+    // Sum of the cards worth plus or minus each value from 1 to 10, which is all an
+    // Invert needs to know to flip them
+    sums: [i8; 10],
+    // Value of the last card that isn't an Invert, which is what a Double copies
+    last: i8,
+    cards: u8,
+    tiebreaker: bool,
+}
+
+impl Side {
+    fn from_board(board: &Board) -> Side {
+        let mut side = Side {
+            sums: [0; 10],
+            last: 0,
+            cards: 0,
+            tiebreaker: board.has_tiebreaker(),
+        };
+
+        for card in &board.cards {
+            if card.special_type == SpecialType::Invert {
+                side.cards += 1;
+            } else {
+                side.add(card.value);
+            }
+        }
+
+        side
+    }
+
+    fn total(&self) -> i8 {
+        self.sums.iter().sum()
+    }
+
+    // Forgets which cards make up the total, once no Invert can flip any of them
+    fn collapse(&mut self) {
+        let total = self.total();
+        self.sums = [0; 10];
+        self.sums[0] = total;
+    }
+
+    fn is_filled(&self) -> bool {
+        self.cards as usize >= TABLE_SIZE && self.total() <= 20
+    }
+
+    fn add(&mut self, value: i8) {
+        // Cards worth 0 can never be flipped, so they only count towards the table
+        if let Some(sum) = (value.unsigned_abs() as usize)
+            .checked_sub(1)
+            .and_then(|index| self.sums.get_mut(index))
+        {
+            *sum += value;
+        }
+        self.last = value;
+        self.cards += 1;
+    }
+
+    // The side after a card from the hand is played on it, following `engine::apply_effect`
+    fn play(mut self, card: &Card, value_index: usize) -> Side {
+        match &card.effect {
+            Some(CardEffect::InvertValues(values)) => {
+                for value in 1..=10 {
+                    if values.iter().any(|flipped| flipped.abs() == value) {
+                        self.sums[value as usize - 1] *= -1;
+                        if self.last.abs() == value {
+                            self.last = -self.last;
+                        }
+                    }
+                }
+                self.cards += 1;
+            }
+            Some(CardEffect::DoubleLast) => self.add(self.last),
+            None if card.has_playstyles() => self.add(card.values_list[value_index]),
+            None => self.add(card.value),
+        }
+
+        self.tiebreaker |= card.special_type == SpecialType::TieBreaker;
+        self
+    }
+}
+
+// Everything about a position the rest of the set depends on
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct State {
+    side: Side,
+    opponent: Side,
+    opponent_standing: bool,
+    // Which of the cards in the hand we started from are still held
+    hand: u16,
+    played_card: bool,
+    deck: [u8; 10],
+    // Cards dealt since the position being solved, up to `EXACT_DRAWS`
+    draws: u8,
+}
+
+// The points in a set the search stops at and remembers the value of
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+enum Node {
+    // Our turn is in progress and we pick what to do
+    Decide,
+    // We are standing and the opponent draws until they catch up or bust
+    Chase,
+    // We ended our turn and it's the opponent's
+    OpponentTurn,
+    // The opponent's turn is over and ours starts with a draw
+    Draw,
+}
+
+// Every action open to a seat with its chance of winning the set, best first. Actions
+// are ranked by their win chance less `CARD_COST` for each side card spent, so one that
+// spends a card can rank below one that wins slightly less often.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution {
+    pub actions: Vec<(Action, f64)>,
+}
+
+impl Solution {
+    pub fn best(&self) -> Action {
+        self.actions
+            .first()
+            .map_or(Action::EndTurn, |&(action, _)| action)
+    }
+}
+
+// Searches every way the rest of a set can go from our side of the table, with our
+// choices maximized and the board deck's draws and the opponent averaged over
+struct Solver<'a> {
+    hand: &'a [Card],
+    rules: &'a Rules,
+    // Who wins a tie when both sides hold a TieBreaker, as in `Game::check_win`
+    first_seat: bool,
+    // The board deck in the position being solved
+    deck: [u8; 10],
+    memo: HashMap<(Node, State), Value>,
+}
+
+impl Solver<'_> {
+    fn actions(&self, state: &State) -> Vec<Action> {
+        let mut actions = vec![Action::Stand, Action::EndTurn];
+
+        if !state.played_card {
+            // Indices stay those of the hand we started from, played cards are masked out
+            actions.extend(engine::card_plays(self.hand).into_iter().filter(|action| {
+                matches!(action, Action::Play { card_index, .. }
+                    if state.hand & (1 << card_index) != 0)
+            }));
+        }
+
+        actions
+    }
+
+    // Whether we win once neither side can change their total any more
+    fn compare(&self, state: &State) -> Value {
+        let (total, opponent_total) = (state.side.total(), state.opponent.total());
+        let tiebreaker = state.side.tiebreaker && (!state.opponent.tiebreaker || self.first_seat);
+
+        let won = total <= 20
+            && (opponent_total > 20
+                || total > opponent_total
+                || (total == opponent_total && tiebreaker));
+        Value::certain(won)
+    }
+
+    // Averages `next` over every card the board deck could deal
+    fn draw(&mut self, state: &State, next: impl Fn(&mut Self, State, i8) -> Value) -> Value {
+        let cards: u32 = state.deck.iter().map(|&count| count as u32).sum();
+        if cards == 0 {
+            // The set is decided on the boards as they are
+            return self.compare(state);
+        }
+
+        let mut value = Value::LOSS;
+        for card in 1..=10 {
+            let count = state.deck[card as usize - 1];
+            if count == 0 {
+                continue;
+            }
+
+            let mut next_state = *state;
+            next_state.draws = (state.draws + 1).min(EXACT_DRAWS);
+            if next_state.draws < EXACT_DRAWS {
+                next_state.deck[card as usize - 1] -= 1;
+            } else {
+                next_state.deck = self.deck;
+            }
+            let chance = f64::from(count) / f64::from(cards);
+            let next_value = next(self, next_state, card);
+            value.score += chance * next_value.score;
+            value.win_chance += chance * next_value.win_chance;
+        }
+        value
+    }
+
+    fn card_cost(&self) -> f64 {
+        match self.rules.hand_policy {
+            HandPolicy::KeepForMatch => CARD_COST,
+            HandPolicy::DealEachSet => 0.0,
+        }
+    }
+
+    fn holds(&self, state: &State, special_type: SpecialType) -> bool {
+        self.hand.iter().enumerate().any(|(index, card)| {
+            state.hand & (1 << index) != 0 && card.special_type == special_type
+        })
+    }
+
+    // Drops everything from a state that can't change the rest of the set, so positions
+    // reached by different paths share one entry in the memo
+    fn key(&self, node: Node, state: &State) -> State {
+        let mut key = *state;
+
+        // The opponent plays no side cards, so only their total can matter
+        key.opponent.collapse();
+        key.opponent.last = 0;
+
+        if node == Node::Chase {
+            key.hand = 0;
+        }
+        if node != Node::Decide {
+            key.played_card = false;
+        }
+        if !self.holds(&key, SpecialType::Invert) {
+            key.side.collapse();
+        }
+        if !self.holds(&key, SpecialType::Double) {
+            key.side.last = 0;
+        }
+        if !self.rules.fill_the_table {
+            key.side.cards = 0;
+            key.opponent.cards = 0;
+        }
+
+        key
+    }
+
+    fn memoized(
+        &mut self,
+        node: Node,
+        state: &State,
+        search: impl Fn(&mut Self) -> Value,
+    ) -> Value {
+        let key = (node, self.key(node, state));
+        if let Some(&value) = self.memo.get(&key) {
+            return value;
+        }
+
+        let value = search(self);
+        self.memo.insert(key, value);
+        value
+    }
+
+    fn action_value(&mut self, state: &State, action: Action) -> Value {
+        match action {
+            Action::Stand if state.side.total() > 20 => Value::LOSS,
+            Action::Stand => self.stand(state),
+            Action::EndTurn if state.side.total() > 20 => Value::LOSS,
+            Action::EndTurn => self.opponent_turn(state),
+            Action::Play {
+                card_index,
+                value_index,
+            } => {
+                let mut next_state = *state;
+                next_state.side = state.side.play(&self.hand[card_index], value_index);
+                next_state.hand &= !(1 << card_index);
+                next_state.played_card = true;
+                let mut value = self.changed(&next_state);
+                value.score -= self.card_cost();
+                value
+            }
+        }
+    }
+
+    // Applies the set's rules after a draw or a played card changes our total
+    fn changed(&mut self, state: &State) -> Value {
+        let total = state.side.total();
+
+        if self.rules.fill_the_table && state.side.is_filled() {
+            Value::WIN
+        } else if total > 20 && state.played_card {
+            // Nothing left to get back under with
+            Value::LOSS
+        } else if total == 20 && self.rules.auto_stand {
+            self.stand(state)
+        } else {
+            self.decide(state)
+        }
+    }
+
+    fn decide(&mut self, state: &State) -> Value {
+        self.memoized(Node::Decide, state, |solver| {
+            let mut best: Option<Value> = None;
+            for action in solver.actions(state) {
+                let value = solver.action_value(state, action);
+                // Ties go to the action listed first, as in `solve`
+                if best.is_none_or(|best| value.score > best.score) {
+                    best = Some(value);
+                }
+            }
+            best.unwrap_or(Value::LOSS)
+        })
+    }
+
+    fn stand(&mut self, state: &State) -> Value {
+        if state.opponent_standing {
+            return self.compare(state);
+        }
+
+        self.memoized(Node::Chase, state, |solver| {
+            solver.draw(state, |solver, mut next, card| {
+                next.opponent.add(card);
+                let opponent_total = next.opponent.total();
+
+                if opponent_total > 20 {
+                    Value::WIN
+                } else if solver.rules.fill_the_table && next.opponent.is_filled() {
+                    Value::LOSS
+                } else if opponent_total >= next.side.total() {
+                    solver.compare(&next)
+                } else {
+                    solver.stand(&next)
+                }
+            })
+        })
+    }
+
+    fn opponent_turn(&mut self, state: &State) -> Value {
+        if state.opponent_standing {
+            return self.next_turn(state);
+        }
+
+        self.memoized(Node::OpponentTurn, state, |solver| {
+            solver.draw(state, |solver, mut next, card| {
+                next.opponent.add(card);
+
+                if next.opponent.total() > 20 {
+                    Value::WIN
+                } else if solver.rules.fill_the_table && next.opponent.is_filled() {
+                    Value::LOSS
+                } else {
+                    next.opponent_standing = next.opponent.total() >= OPPONENT_STAND_THRESHOLD;
+                    solver.next_turn(&next)
+                }
+            })
+        })
+    }
+
+    fn next_turn(&mut self, state: &State) -> Value {
+        self.memoized(Node::Draw, state, |solver| {
+            solver.draw(state, |solver, mut next, card| {
+                next.side.add(card);
+                next.played_card = false;
+                solver.changed(&next)
+            })
+        })
+    }
+}
+
+// Works out the chance of winning the set with every action open to the view's seat
+// during its turn, under the view's rules, assuming we go on playing the best line and
+// the opponent as described above. Only the next `EXACT_DRAWS` draws are counted exactly,
+// so lines that go deeper are approximate. The hand may hold at most `HAND_SIZE` cards.
+pub fn solve(view: &PlayerView) -> Solution {
+    let state = State {
+        side: Side::from_board(&view.board),
+        opponent: Side::from_board(&view.opponent_board),
+        opponent_standing: view.opponent_status == Status::Standing,
+        hand: (1 << view.hand.len()) - 1,
+        played_card: view.played_card,
+        deck: DeckCounts::from_view(view).0,
+        draws: 0,
+    };
+
+    let mut solver = Solver {
+        hand: &view.hand,
+        rules: &view.rules,
+        first_seat: view.seat == 0,
+        deck: state.deck,
+        memo: HashMap::new(),
+    };
+
+    let mut actions: Vec<(Action, Value)> = solver
+        .actions(&state)
+        .into_iter()
+        .map(|action| (action, solver.action_value(&state, action)))
+        .collect();
+    // Ties keep the order actions are listed in, so standing is preferred to drawing more
+    actions.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+
+    Solution {
+        actions: actions
+            .into_iter()
+            .map(|(action, value)| (action, value.win_chance))
+            .collect(),
+    }
+}
+
+// Plays every action the solver rates best, under the rules of the match it is playing
+#[derive(Default)]
+pub struct SolverAgent;
+
+impl Agent for SolverAgent {
+    fn name(&self) -> &str {
+        "ai:solver"
+    }
+
+    fn choose_action(&self, view: &PlayerView) -> Action {
+        solve(view).best()
+    }
+}
+
+// A position to solve, as read by the "solve" command. Cards dealt from the board
+// deck are plain numbers like "7", side cards are written in deck notation with a
+// leading sign like "+3" and "-2", and a card played with a chosen value is
+// written with it after an "=", e.g. "+1/-1T=-1".
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Position {
+    #[serde(default)]
+    pub seat: PlayerId,
+    pub board: Vec<String>,
+    pub hand: Vec<String>,
+    pub opponent_board: Vec<String>,
+    #[serde(default)]
+    pub opponent_hand_size: usize,
+    #[serde(default)]
+    pub opponent_standing: bool,
+    #[serde(default)]
+    pub played_card: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PositionError {
+    Format(String),
+    InvalidCard(String),
+    InvalidValue(String),
+    // More cards in the hand than are ever dealt into one
+    HandTooLarge(usize),
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionError::Format(error) => write!(f, "Invalid position: {}", error),
+            PositionError::InvalidCard(card) => write!(f, "Invalid card: '{}'", card),
+            PositionError::InvalidValue(card) => {
+                write!(f, "'{}' can't be played with that value", card)
+            }
+            PositionError::HandTooLarge(size) => write!(
+                f,
+                "A hand holds at most {} cards, found {}",
+                HAND_SIZE, size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+// Lays cards out on a board in order, applying their effects as the engine would
+fn read_board(cards: &[String]) -> Result<Board, PositionError> {
+    let mut board = Board::default();
+
+    for entry in cards {
+        let (notation, value) = match entry.split_once('=') {
+            Some((notation, value)) => (notation.trim(), Some(value.trim())),
+            None => (entry.trim(), None),
+        };
+        let card =
+            Card::from_string(notation).ok_or_else(|| PositionError::InvalidCard(entry.clone()))?;
+
+        let is_drawn = notation.chars().all(|c| c.is_ascii_digit());
+        if is_drawn {
+            board.draw(card);
+            continue;
+        }
+
+        let value_index = match value {
+            Some(value) => {
+                let value = value
+                    .parse::<i8>()
+                    .map_err(|_| PositionError::InvalidValue(entry.clone()))?;
+                card.values_list
+                    .iter()
+                    .position(|&option| option == value)
+                    .filter(|_| card.has_playstyles())
+                    .ok_or_else(|| PositionError::InvalidValue(entry.clone()))?
+            }
+            None => 0,
+        };
+        engine::place_card(&mut board, card, value_index);
+    }
+
+    Ok(board)
+}
+
+impl Position {
+    pub fn parse(content: &str) -> Result<Position, PositionError> {
+        toml::from_str(content).map_err(|error| PositionError::Format(error.message().to_string()))
+    }
+
+    // The position as its seat would see it mid-turn, in a match played under `rules`
+    pub fn to_view(&self, rules: Rules) -> Result<PlayerView, PositionError> {
+        if self.hand.len() > HAND_SIZE {
+            return Err(PositionError::HandTooLarge(self.hand.len()));
+        }
+
+        let board = read_board(&self.board)?;
+        let opponent_board = read_board(&self.opponent_board)?;
+        let hand = self
+            .hand
+            .iter()
+            .map(|card| {
+                Card::from_string(card).ok_or_else(|| PositionError::InvalidCard(card.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut view = PlayerView {
+            seat: self.seat.min(1),
+            hand,
+            opponent_hand_size: self.opponent_hand_size,
+            board,
+            opponent_board,
+            status: Status::Playing,
+            opponent_status: if self.opponent_standing {
+                Status::Standing
+            } else {
+                Status::Playing
+            },
+            played_card: self.played_card,
+            score: [0, 0],
+            round: 1,
+            deck_remaining: 0,
+            rules,
+        };
+        view.deck_remaining = DeckCounts::from_view(&view).total();
+
+        Ok(view)
+    }
+}
@@ -0,0 +1,715 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    agent,
+    cards::Match,
+    deck::{self, DeckError, DeckFile, DeckRules},
+    rules::Rules,
+    simulate,
+};
+
+// Bumped whenever the layout of a saved tournament changes
+pub const TOURNAMENT_STATE_VERSION: u32 = 2;
+
+pub const STARTING_ELO: f64 = 1500.0;
+// How far a single match moves an Elo rating
+pub const ELO_K: f64 = 32.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TournamentFormat {
+    // Every entrant plays every other entrant once
+    RoundRobin,
+    // Entrants with the same record are paired each round, without rematches where possible
+    Swiss,
+    // Winners go through to the next round until one is left, seeded in entrant order
+    SingleElimination,
+}
+
+impl fmt::Display for TournamentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TournamentFormat::RoundRobin => write!(f, "round-robin"),
+            TournamentFormat::Swiss => write!(f, "swiss"),
+            TournamentFormat::SingleElimination => write!(f, "single-elimination"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entrant {
+    pub name: String,
+    // Deck file path, relative to the config file
+    pub deck: String,
+    // Built-in agent name, as accepted by `agent::from_name`
+    pub agent: String,
+}
+
+// A tournament as described by its TOML config file
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TournamentConfig {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub format: TournamentFormat,
+    // Number of Swiss rounds, defaults to enough to find a single unbeaten entrant
+    #[serde(default)]
+    pub rounds: Option<usize>,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub rules: Rules,
+    pub entrants: Vec<Entrant>,
+}
+
+#[derive(Debug)]
+pub enum TournamentError {
+    Io(io::Error),
+    Config(String),
+    State(serde_json::Error),
+    Deck { entrant: String, error: DeckError },
+    UnknownAgent(String),
+    DuplicateEntrant(String),
+    TooFewEntrants,
+    // A saved tournament was started from a different config
+    StateMismatch,
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TournamentError::Io(error) => write!(f, "Unable to access tournament file: {}", error),
+            TournamentError::Config(error) => write!(f, "Invalid tournament config: {}", error),
+            TournamentError::State(error) => write!(f, "Invalid tournament state: {}", error),
+            TournamentError::Deck { entrant, error } => {
+                write!(f, "Invalid deck for '{}': {}", entrant, error)
+            }
+            TournamentError::UnknownAgent(name) => write!(f, "Unknown agent: '{}'", name),
+            TournamentError::DuplicateEntrant(name) => {
+                write!(f, "Entrant '{}' is listed more than once", name)
+            }
+            TournamentError::TooFewEntrants => write!(f, "A tournament needs at least 2 entrants"),
+            TournamentError::StateMismatch => {
+                write!(
+                    f,
+                    "The saved tournament was started from a different config"
+                )
+            }
+            TournamentError::UnsupportedVersion(version) => write!(
+                f,
+                "Tournament state version {} is not supported, expected {}",
+                version, TOURNAMENT_STATE_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TournamentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TournamentError::Io(error) => Some(error),
+            TournamentError::State(error) => Some(error),
+            TournamentError::Deck { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TournamentError {
+    fn from(error: io::Error) -> Self {
+        TournamentError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for TournamentError {
+    fn from(error: serde_json::Error) -> Self {
+        TournamentError::State(error)
+    }
+}
+
+// One match of the tournament, or a bye, once it has been played
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub round: usize,
+    // Entrant indexes. `home` plays the first seat and `away` is None for a bye.
+    pub home: usize,
+    pub away: Option<usize>,
+    // None for a drawn match
+    pub winner: Option<usize>,
+    // Sets won by home and away
+    pub score: [u8; 2],
+}
+
+// What is written to the state file after every match so a tournament can be resumed
+#[derive(Serialize, Deserialize)]
+struct TournamentState {
+    version: u32,
+    format: TournamentFormat,
+    seed: u64,
+    entrants: Vec<String>,
+    results: Vec<MatchResult>,
+}
+
+// An entrant's record so far
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub entrant: usize,
+    pub played: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    pub sets_won: usize,
+    pub sets_lost: usize,
+    pub elo: f64,
+}
+
+impl Standing {
+    pub fn set_diff(&self) -> i64 {
+        self.sets_won as i64 - self.sets_lost as i64
+    }
+}
+
+// The chance the Elo model gives a player rated `rating` of beating one rated `opponent`
+fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+pub struct Tournament {
+    pub config: TournamentConfig,
+    pub decks: Vec<DeckFile>,
+    pub results: Vec<MatchResult>,
+}
+
+impl Tournament {
+    // Reads a config file and every entrant's deck, relative to the config's directory
+    pub fn load(config_path: impl AsRef<Path>) -> Result<Tournament, TournamentError> {
+        let config_path = config_path.as_ref();
+        let config: TournamentConfig = toml::from_str(&fs::read_to_string(config_path)?)
+            .map_err(|error| TournamentError::Config(error.message().to_string()))?;
+
+        if config.entrants.len() < 2 {
+            return Err(TournamentError::TooFewEntrants);
+        }
+
+        let mut names = HashSet::new();
+        for entrant in &config.entrants {
+            if !names.insert(&entrant.name) {
+                return Err(TournamentError::DuplicateEntrant(entrant.name.clone()));
+            }
+            if agent::from_name(&entrant.agent).is_none() {
+                return Err(TournamentError::UnknownAgent(entrant.agent.clone()));
+            }
+        }
+
+        let directory = config_path.parent().unwrap_or(Path::new(""));
+        let decks = config
+            .entrants
+            .iter()
+            .map(|entrant| {
+                deck::read_deck_file(directory.join(&entrant.deck), &DeckRules::kotor()).map_err(
+                    |error| TournamentError::Deck {
+                        entrant: entrant.name.clone(),
+                        error,
+                    },
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Tournament {
+            config,
+            decks,
+            results: vec![],
+        })
+    }
+
+    // Where a tournament's progress is saved, next to its config
+    pub fn state_path(config_path: impl AsRef<Path>) -> PathBuf {
+        config_path.as_ref().with_extension("state.json")
+    }
+
+    pub fn save_state(&self, path: impl AsRef<Path>) -> Result<(), TournamentError> {
+        let state = TournamentState {
+            version: TOURNAMENT_STATE_VERSION,
+            format: self.config.format,
+            seed: self.config.seed,
+            entrants: self.entrant_names(),
+            results: self.results.clone(),
+        };
+
+        fs::write(path, serde_json::to_string_pretty(&state)?)?;
+        Ok(())
+    }
+
+    // Picks up the results saved by an interrupted run of the same config
+    pub fn load_state(&mut self, path: impl AsRef<Path>) -> Result<(), TournamentError> {
+        let state: TournamentState = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        if state.version != TOURNAMENT_STATE_VERSION {
+            return Err(TournamentError::UnsupportedVersion(state.version));
+        }
+        if state.format != self.config.format
+            || state.seed != self.config.seed
+            || state.entrants != self.entrant_names()
+        {
+            return Err(TournamentError::StateMismatch);
+        }
+
+        self.results = state.results;
+        Ok(())
+    }
+
+    fn entrant_names(&self) -> Vec<String> {
+        self.config
+            .entrants
+            .iter()
+            .map(|entrant| entrant.name.clone())
+            .collect()
+    }
+
+    // Plays every match not already in `results`, round by round, calling `on_result`
+    // after each one so progress can be saved and shown
+    pub fn run(
+        &mut self,
+        mut on_result: impl FnMut(&Tournament, &MatchResult) -> Result<(), TournamentError>,
+    ) -> Result<(), TournamentError> {
+        let mut round = 1;
+
+        while let Some(pairings) = self.pairings(round) {
+            for (index, &(home, away)) in pairings.iter().enumerate() {
+                let played = self.results.iter().any(|result| {
+                    result.round == round && result.home == home && result.away == away
+                });
+                if played {
+                    continue;
+                }
+
+                let result = match away {
+                    Some(away) => self.play(round, index, home, away),
+                    None => MatchResult {
+                        round,
+                        home,
+                        away: None,
+                        winner: Some(home),
+                        score: [0, 0],
+                    },
+                };
+                self.results.push(result.clone());
+                on_result(self, &result)?;
+            }
+            round += 1;
+        }
+
+        Ok(())
+    }
+
+    // Every match is seeded from the tournament seed and its place in the schedule, so a
+    // resumed tournament plays out exactly as an uninterrupted one would have
+    fn play(&self, round: usize, index: usize, home: usize, away: usize) -> MatchResult {
+        let mut rng = ChaCha12Rng::seed_from_u64(self.config.seed);
+        rng.set_stream(((round as u64) << 32) | index as u64);
+
+        let home_deck = self.decks[home].shuffled(&mut rng);
+        let away_deck = self.decks[away].shuffled(&mut rng);
+        let mut pazaak_match = Match::new(home_deck, away_deck, rng);
+        pazaak_match.rules = self.config.rules;
+
+        let entrants = &self.config.entrants;
+        let home_agent = agent::from_name(&entrants[home].agent).expect("checked when loaded");
+        let away_agent = agent::from_name(&entrants[away].agent).expect("checked when loaded");
+        let record = simulate::play_match(
+            &mut pazaak_match,
+            [home_agent.as_ref(), away_agent.as_ref()],
+        );
+
+        MatchResult {
+            round,
+            home,
+            away: Some(away),
+            winner: record.winner.map(|seat| [home, away][seat]),
+            score: record.score,
+        }
+    }
+
+    // The home and away entrants of every match in a round, worked out from the results
+    // of the rounds before it. None once the tournament is over.
+    fn pairings(&self, round: usize) -> Option<Vec<(usize, Option<usize>)>> {
+        let entrants = self.config.entrants.len();
+
+        match self.config.format {
+            TournamentFormat::RoundRobin => round_robin_round(entrants, round),
+            TournamentFormat::Swiss => {
+                let rounds = self
+                    .config
+                    .rounds
+                    .unwrap_or_else(|| entrants.next_power_of_two().trailing_zeros() as usize);
+                (round <= rounds).then(|| self.swiss_round(round))
+            }
+            TournamentFormat::SingleElimination => self.elimination_round(round),
+        }
+    }
+
+    // Pairs entrants in standings order with the closest entrant they haven't played yet,
+    // after giving a bye to the lowest ranked entrant who hasn't had one
+    fn swiss_round(&self, round: usize) -> Vec<(usize, Option<usize>)> {
+        let earlier: Vec<&MatchResult> = self
+            .results
+            .iter()
+            .filter(|result| result.round < round)
+            .collect();
+        let mut order: Vec<usize> = self
+            .standings_of(&earlier)
+            .iter()
+            .map(|standing| standing.entrant)
+            .collect();
+
+        let mut bye = None;
+        if order.len() % 2 == 1 {
+            let had_bye = |entrant| {
+                earlier
+                    .iter()
+                    .any(|result| result.away.is_none() && result.home == entrant)
+            };
+            let index = order
+                .iter()
+                .rposition(|&entrant| !had_bye(entrant))
+                .unwrap_or(order.len() - 1);
+            bye = Some(order.remove(index));
+        }
+
+        let met = |a: usize, b: usize| {
+            earlier.iter().any(|result| {
+                (result.home, result.away) == (a, Some(b))
+                    || (result.home, result.away) == (b, Some(a))
+            })
+        };
+        // Rematches are only allowed once every other pairing has been played
+        let matches = pair_without_rematches(&order, &met)
+            .unwrap_or_else(|| order.chunks(2).map(|pair| (pair[0], pair[1])).collect());
+
+        matches
+            .into_iter()
+            .map(|(home, away)| (home, Some(away)))
+            .chain(bye.map(|entrant| (entrant, None)))
+            .collect()
+    }
+
+    // Round 1 follows the seeded bracket, later rounds pair up the previous round's
+    // winners in bracket order
+    fn elimination_round(&self, round: usize) -> Option<Vec<(usize, Option<usize>)>> {
+        let mut alive: Vec<Option<usize>> = bracket_order(self.config.entrants.len());
+
+        for earlier in 1..round {
+            if alive.len() <= 1 {
+                return None;
+            }
+            alive = alive
+                .chunks(2)
+                .map(|pair| {
+                    let (home, away) = (pair[0]?, pair[1]);
+                    let Some(away) = away else {
+                        return Some(home);
+                    };
+                    let result = self.results.iter().find(|result| {
+                        result.round == earlier && result.home == home && result.away == Some(away)
+                    })?;
+                    Some(elimination_winner(result))
+                })
+                .collect();
+
+            // A round that isn't finished can't be followed by another
+            if alive.iter().any(Option::is_none) {
+                return None;
+            }
+        }
+
+        if alive.len() <= 1 {
+            return None;
+        }
+
+        Some(
+            alive
+                .chunks(2)
+                .filter_map(|pair| Some((pair[0]?, pair[1])))
+                .collect(),
+        )
+    }
+
+    // Every entrant's record, best first: by wins, then set differential, then Elo
+    pub fn standings(&self) -> Vec<Standing> {
+        self.standings_of(&self.results.iter().collect::<Vec<_>>())
+    }
+
+    fn standings_of(&self, results: &[&MatchResult]) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = (0..self.config.entrants.len())
+            .map(|entrant| Standing {
+                entrant,
+                played: 0,
+                wins: 0,
+                losses: 0,
+                draws: 0,
+                sets_won: 0,
+                sets_lost: 0,
+                elo: STARTING_ELO,
+            })
+            .collect();
+
+        for result in results {
+            // A bye counts as a win, with no sets played and no change in Elo
+            let Some(away) = result.away else {
+                standings[result.home].played += 1;
+                standings[result.home].wins += 1;
+                continue;
+            };
+            let home = result.home;
+
+            let home_score = match result.winner {
+                Some(winner) if winner == home => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+            let expected = expected_score(standings[home].elo, standings[away].elo);
+            let change = ELO_K * (home_score - expected);
+
+            for (entrant, score, won, lost, change) in [
+                (home, home_score, result.score[0], result.score[1], change),
+                (
+                    away,
+                    1.0 - home_score,
+                    result.score[1],
+                    result.score[0],
+                    -change,
+                ),
+            ] {
+                let standing = &mut standings[entrant];
+                standing.played += 1;
+                standing.sets_won += won as usize;
+                standing.sets_lost += lost as usize;
+                standing.elo += change;
+                if score == 1.0 {
+                    standing.wins += 1;
+                } else if score == 0.0 {
+                    standing.losses += 1;
+                } else {
+                    standing.draws += 1;
+                }
+            }
+        }
+
+        standings.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(b.set_diff().cmp(&a.set_diff()))
+                .then(b.elo.total_cmp(&a.elo))
+                .then(a.entrant.cmp(&b.entrant))
+        });
+        standings
+    }
+
+    // The standings as CSV, one row per entrant in standings order
+    pub fn standings_csv(&self) -> String {
+        let mut csv = "rank,name,agent,played,wins,losses,draws,set_diff,elo\n".to_string();
+
+        for (rank, standing) in self.standings().iter().enumerate() {
+            let entrant = &self.config.entrants[standing.entrant];
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{:.0}\n",
+                rank + 1,
+                csv_field(&entrant.name),
+                csv_field(&entrant.agent),
+                standing.played,
+                standing.wins,
+                standing.losses,
+                standing.draws,
+                standing.set_diff(),
+                standing.elo
+            ));
+        }
+
+        csv
+    }
+
+    // Names a match uniquely among the tournaments played from one config, e.g.
+    // "seed 7, round 2: Revan vs Bastila". A restarted tournament gives its matches the
+    // same ids, since they play out the same way.
+    pub fn match_id(&self, result: &MatchResult) -> String {
+        let name = |entrant: usize| &self.config.entrants[entrant].name;
+
+        match result.away {
+            Some(away) => format!(
+                "seed {}, round {}: {} vs {}",
+                self.config.seed,
+                result.round,
+                name(result.home),
+                name(away)
+            ),
+            None => format!(
+                "seed {}, round {}: {} has a bye",
+                self.config.seed,
+                result.round,
+                name(result.home)
+            ),
+        }
+    }
+
+    // Describes a result for progress output, e.g. "Round 2: Revan 3-1 Bastila"
+    pub fn describe(&self, result: &MatchResult) -> String {
+        let name = |entrant: usize| &self.config.entrants[entrant].name;
+
+        match result.away {
+            Some(away) => format!(
+                "Round {}: {} {}-{} {}",
+                result.round,
+                name(result.home),
+                result.score[0],
+                result.score[1],
+                name(away)
+            ),
+            None => format!("Round {}: {} has a bye", result.round, name(result.home)),
+        }
+    }
+}
+
+impl fmt::Display for Tournament {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .config
+            .entrants
+            .iter()
+            .map(|entrant| entrant.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+
+        writeln!(
+            f,
+            "{:>4}  {:<width$}  {:>6}  {:>3}  {:>3}  {:>3}  {:>5}  {:>5}",
+            "Rank", "Name", "Played", "W", "L", "D", "Sets", "Elo"
+        )?;
+        for (rank, standing) in self.standings().iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<width$}  {:>6}  {:>3}  {:>3}  {:>3}  {:>+5}  {:>5.0}",
+                rank + 1,
+                self.config.entrants[standing.entrant].name,
+                standing.played,
+                standing.wins,
+                standing.losses,
+                standing.draws,
+                standing.set_diff(),
+                standing.elo
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// Quotes a CSV field if it holds anything that would break the row
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// The circle method: the first seat stays put while everyone else rotates a place each
+// round. With an odd number of entrants the empty seat is the one that stays put, and
+// whoever is paired with it sits the round out.
+fn round_robin_round(entrants: usize, round: usize) -> Option<Vec<(usize, Option<usize>)>> {
+    let seats = entrants + entrants % 2;
+    if round == 0 || round >= seats {
+        return None;
+    }
+
+    let mut circle: Vec<usize> = (0..seats).collect();
+    if entrants % 2 == 1 {
+        circle.rotate_right(1);
+    }
+    circle[1..].rotate_right(round - 1);
+
+    Some(
+        (0..seats / 2)
+            .filter_map(|index| {
+                let (a, b) = (circle[index], circle[seats - 1 - index]);
+                // Everyone in a moving seat changes place each round, so seating by place
+                // alternates who plays first, while the fixed seat alternates by round
+                let first = if index == 0 { round } else { index };
+                let (home, away) = if first.is_multiple_of(2) {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                (home < entrants && away < entrants).then_some((home, Some(away)))
+            })
+            .collect(),
+    )
+}
+
+// Pairs entrants off in order so nobody meets someone they have already played,
+// backtracking when the closest pairing leaves the rest unpairable
+fn pair_without_rematches(
+    order: &[usize],
+    met: &impl Fn(usize, usize) -> bool,
+) -> Option<Vec<(usize, usize)>> {
+    let Some((&first, rest)) = order.split_first() else {
+        return Some(vec![]);
+    };
+
+    for (index, &opponent) in rest.iter().enumerate() {
+        if met(first, opponent) {
+            continue;
+        }
+
+        let mut remaining = rest.to_vec();
+        remaining.remove(index);
+        if let Some(mut pairings) = pair_without_rematches(&remaining, met) {
+            pairings.insert(0, (first, opponent));
+            return Some(pairings);
+        }
+    }
+
+    None
+}
+
+// Seeds in bracket order, so the top seeds can only meet in the last rounds, with None
+// for the byes that fill the bracket out to a power of two
+fn bracket_order(entrants: usize) -> Vec<Option<usize>> {
+    let size = entrants.next_power_of_two();
+    let mut order = vec![0];
+
+    while order.len() < size {
+        let length = order.len() * 2;
+        order = order
+            .iter()
+            .flat_map(|&seed| [seed, length - 1 - seed])
+            .collect();
+    }
+
+    order
+        .into_iter()
+        .map(|seed| (seed < entrants).then_some(seed))
+        .collect()
+}
+
+// A drawn knockout match goes to whoever won more sets, then to the higher seed
+fn elimination_winner(result: &MatchResult) -> usize {
+    let away = result.away.expect("byes are decided without a result");
+
+    result
+        .winner
+        .unwrap_or(match result.score[0].cmp(&result.score[1]) {
+            std::cmp::Ordering::Less => away,
+            std::cmp::Ordering::Greater => result.home,
+            std::cmp::Ordering::Equal => result.home.min(away),
+        })
+}
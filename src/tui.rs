@@ -0,0 +1,424 @@
+use crossterm::{
+    cursor,
+    event::{self, Event as TermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Print, Stylize},
+    terminal::{self, ClearType},
+};
+use pazaak::{
+    advisor::{self, Hint},
+    Action, Card, PlayerView, RuleError,
+};
+use std::io::{self, Stdout, Write};
+
+use crate::{
+    messages::{self, get_event_message, hint_label, Event},
+    ui::{Choice, Ui},
+    DEFAULT_SAVE_PATH,
+};
+
+// Narrowest a card box is drawn, including its borders. Boxes widen to fit longer labels,
+// such as a TieBreaker's "[+1]/-1T".
+const MIN_CARD_WIDTH: usize = 9;
+
+const KEY_HELP: &str =
+    "←/→ pick a card  enter play  s stand  e end turn  w save  ↑/↓ scroll log  q quit";
+
+// A full-screen interface drawn on crossterm's alternate screen
+pub struct TerminalUi {
+    stdout: Stdout,
+    log: Vec<String>,
+    // How many lines the log is scrolled back from the newest entry
+    log_scroll: usize,
+    view: Option<PlayerView>,
+    // Whether the player at the keyboard is picking an action, which shows their selection
+    prompting: bool,
+    selected_card: usize,
+    // Set while picking which value to play a Flip or TieBreaker card with
+    selected_value: Option<usize>,
+    // Whether to show the advisor's odds while the player picks an action
+    show_hints: bool,
+    // The advisor's odds for the action being picked, worked out once per prompt
+    hints: Vec<Hint>,
+}
+
+// Number of columns a string takes up on screen, skipping its color escape codes
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+
+    for c in text.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => width += 1,
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+        }
+    }
+
+    width
+}
+
+// Draws cards as a grid of boxes, as many to a row as fit in `width`
+fn card_grid(cards: &[String], selected: Option<usize>, width: usize) -> Vec<String> {
+    let inner_width = cards
+        .iter()
+        .map(|card| visible_width(card))
+        .max()
+        .unwrap_or(0)
+        .max(MIN_CARD_WIDTH - 2);
+    let per_row = (width / (inner_width + 2)).max(1);
+    let top_border = format!("┌{}┐", "─".repeat(inner_width));
+    let bottom_border = format!("└{}┘", "─".repeat(inner_width));
+    let side = "│";
+    let mut lines = vec![];
+
+    for (row, chunk) in cards.chunks(per_row).enumerate() {
+        let mut top = String::new();
+        let mut middle = String::new();
+        let mut bottom = String::new();
+
+        for (column, card) in chunk.iter().enumerate() {
+            let padding = inner_width - visible_width(card);
+            let left = padding / 2;
+            let content = format!("{}{}{}", " ".repeat(left), card, " ".repeat(padding - left));
+
+            if selected == Some(row * per_row + column) {
+                top.push_str(&top_border.as_str().yellow().bold().to_string());
+                middle.push_str(&format!("{}{}{}", side.yellow(), content, side.yellow()));
+                bottom.push_str(&bottom_border.as_str().yellow().bold().to_string());
+            } else {
+                top.push_str(&top_border);
+                middle.push_str(&format!("{}{}{}", side, content, side));
+                bottom.push_str(&bottom_border);
+            }
+        }
+
+        lines.extend([top, middle, bottom]);
+    }
+
+    lines
+}
+
+fn card_strings(cards: &[Card]) -> Vec<String> {
+    cards.iter().map(|card| card.to_string()).collect()
+}
+
+impl TerminalUi {
+    pub fn new(show_hints: bool) -> io::Result<TerminalUi> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+        Ok(TerminalUi {
+            stdout,
+            log: vec![],
+            log_scroll: 0,
+            prompting: false,
+            view: None,
+            selected_card: 0,
+            selected_value: None,
+            show_hints,
+            hints: vec![],
+        })
+    }
+
+    fn table_lines(&self, view: &PlayerView, width: usize) -> Vec<String> {
+        let opponent = view.opponent();
+        let mut lines = vec![];
+
+        lines.push(format!(
+            "{}  Round {}  You: {}  | Opponent: {}",
+            "pazaak-rs".red().bold(),
+            view.round.to_string().yellow().bold(),
+            view.score[view.seat].to_string().green().bold(),
+            view.score[opponent].to_string().red().bold()
+        ));
+        lines.push(format!("Board deck: {} cards left", view.deck_remaining));
+        lines.push(String::new());
+
+        // Start with the opponent's side of the table
+        lines.push(format!(
+            "Opponent ({})  Hand: {}",
+            view.opponent_status,
+            vec!["?"; view.opponent_hand_size].join(" ")
+        ));
+        if view.opponent_board.cards.is_empty() {
+            lines.push("<Empty Board>".yellow().italic().to_string());
+        } else {
+            lines.extend(card_grid(
+                &card_strings(&view.opponent_board.cards),
+                None,
+                width,
+            ));
+        }
+        lines.push(format!("Total: {}", view.opponent_board.total()));
+        lines.push("~".repeat(width.min(60)).blue().bold().to_string());
+
+        // Then, your side of the table and your hand
+        lines.push(format!("You ({})", view.status));
+        if view.board.cards.is_empty() {
+            lines.push("<Empty Board>".yellow().italic().to_string());
+        } else {
+            lines.extend(card_grid(&card_strings(&view.board.cards), None, width));
+        }
+        lines.push(format!("Total: {}", view.board.total()));
+        if self.prompting && view.needs_rescue() {
+            lines.push(messages::RESCUE_MESSAGE.red().bold().to_string());
+        }
+        lines.push(String::new());
+
+        lines.push("Your Hand:".to_string());
+        if view.hand.is_empty() {
+            lines.push("<Empty Hand>".yellow().italic().to_string());
+        } else {
+            let selected = self.prompting.then_some(self.selected_card);
+            lines.extend(card_grid(&card_strings(&view.hand), selected, width));
+        }
+
+        // Offer each way of playing the selected card while one is being chosen
+        if let (Some(value_index), Some(card)) =
+            (self.selected_value, view.hand.get(self.selected_card))
+        {
+            let values = card
+                .values_list
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let value = format!(" {:+} ", value);
+                    if i == value_index {
+                        value.black().on_yellow().to_string()
+                    } else {
+                        value
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            lines.push(format!(
+                "Play {} as: {}  (enter to play, esc to cancel)",
+                card, values
+            ));
+        }
+
+        if self.prompting {
+            lines.extend(self.hint_lines(view));
+        }
+
+        lines
+    }
+
+    // The advisor's odds for playing no card and for the selected way of playing the selected card
+    fn hint_lines(&self, view: &PlayerView) -> Vec<String> {
+        let selected = Action::Play {
+            card_index: self.selected_card,
+            value_index: self.selected_value.unwrap_or(0),
+        };
+
+        self.hints
+            .iter()
+            .filter(|hint| hint.action.is_none() || hint.action == Some(selected))
+            .map(|hint| {
+                format!("{}: {}", hint_label(view, hint), hint)
+                    .yellow()
+                    .italic()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn render(&mut self, prompt: &str) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+
+        let mut lines = match &self.view {
+            Some(view) => self.table_lines(view, width),
+            None => vec![],
+        };
+        lines.push("-".repeat(width.min(60)).blue().bold().to_string());
+
+        // Fill whatever is left above the footer with the newest log lines
+        let log_height = height.saturating_sub(lines.len() + 2);
+        self.log_scroll = self
+            .log_scroll
+            .min(self.log.len().saturating_sub(log_height));
+        let end = self.log.len() - self.log_scroll;
+        let start = end.saturating_sub(log_height);
+        for entry in &self.log[start..end] {
+            let entry: String = entry.chars().take(width).collect();
+            lines.push(format!("{} {}", "~".dark_grey(), entry.dark_grey()));
+        }
+        while lines.len() < height.saturating_sub(2) {
+            lines.push(String::new());
+        }
+
+        lines.push(prompt.to_string());
+        lines.push(
+            KEY_HELP
+                .chars()
+                .take(width)
+                .collect::<String>()
+                .italic()
+                .to_string(),
+        );
+
+        queue!(self.stdout, terminal::Clear(ClearType::All))?;
+        for (row, line) in lines.iter().take(height).enumerate() {
+            queue!(self.stdout, cursor::MoveTo(0, row as u16), Print(line))?;
+        }
+        self.stdout.flush()
+    }
+
+    // Waits for a key press, redrawing if the terminal is resized
+    fn read_key(&mut self, prompt: &str) -> io::Result<KeyCode> {
+        loop {
+            match event::read()? {
+                TermEvent::Key(KeyEvent {
+                    code,
+                    modifiers,
+                    kind: KeyEventKind::Press,
+                    ..
+                }) => {
+                    // Raw mode swallows ctrl-c, so treat it as quitting
+                    if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('c') {
+                        return Ok(KeyCode::Char('q'));
+                    }
+                    return Ok(code);
+                }
+                TermEvent::Resize(..) => self.render(prompt)?,
+                _ => {}
+            }
+        }
+    }
+
+    // Shows a message and waits for any key so the table can be read
+    fn pause(&mut self, view: &PlayerView, message: &str) {
+        self.view = Some(view.clone());
+        self.log.push(message.to_string());
+        self.log_scroll = 0;
+
+        let prompt = format!("{} {}", message.bold(), "(press any key)".yellow().italic());
+        if self.render(&prompt).is_ok() {
+            let _ = self.read_key(&prompt);
+        }
+    }
+
+    fn take_key_action(&mut self, view: &PlayerView) -> io::Result<Choice> {
+        let prompt = format!("Player {}, what would you like to do?", view.seat + 1);
+
+        loop {
+            self.render(&prompt)?;
+            let key = self.read_key(&prompt)?;
+
+            // Picking how to play a Flip or TieBreaker card
+            if let Some(value_index) = self.selected_value {
+                let values_count = view.hand[self.selected_card].values_list.len();
+                match key {
+                    KeyCode::Left => self.selected_value = Some(value_index.saturating_sub(1)),
+                    KeyCode::Right => {
+                        self.selected_value = Some((value_index + 1).min(values_count - 1))
+                    }
+                    KeyCode::Enter => {
+                        self.selected_value = None;
+                        self.log(&get_event_message(view.seat, Event::Play));
+                        return Ok(Choice::Act(Action::Play {
+                            card_index: self.selected_card,
+                            value_index,
+                        }));
+                    }
+                    KeyCode::Esc => self.selected_value = None,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match key {
+                KeyCode::Left => self.selected_card = self.selected_card.saturating_sub(1),
+                KeyCode::Right if self.selected_card + 1 < view.hand.len() => {
+                    self.selected_card += 1
+                }
+                KeyCode::Enter => {
+                    if view.played_card {
+                        self.log(&RuleError::AlreadyPlayed.to_string());
+                    } else if view.hand.is_empty() {
+                        self.log(messages::EMPTY_HAND_MESSAGE);
+                    } else if view.hand[self.selected_card].has_playstyles() {
+                        self.selected_value = Some(0);
+                    } else {
+                        self.log(&get_event_message(view.seat, Event::Play));
+                        return Ok(Choice::Act(Action::Play {
+                            card_index: self.selected_card,
+                            value_index: 0,
+                        }));
+                    }
+                }
+                KeyCode::Char('s') => {
+                    self.log(&get_event_message(view.seat, Event::Stand));
+                    return Ok(Choice::Act(Action::Stand));
+                }
+                KeyCode::Char('e') => {
+                    self.log(&get_event_message(view.seat, Event::EndTurn));
+                    return Ok(Choice::Act(Action::EndTurn));
+                }
+                KeyCode::Char('w') => return Ok(Choice::Save(DEFAULT_SAVE_PATH.to_string())),
+                KeyCode::Up => self.log_scroll += 1,
+                KeyCode::Down => self.log_scroll = self.log_scroll.saturating_sub(1),
+                KeyCode::PageUp => self.log_scroll += 5,
+                KeyCode::PageDown => self.log_scroll = self.log_scroll.saturating_sub(5),
+                KeyCode::Char('q') => return Ok(Choice::Quit),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Drop for TerminalUi {
+    fn drop(&mut self) {
+        // Hand the terminal back the way we found it, even when unwinding from a panic
+        let _ = execute!(self.stdout, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl Ui for TerminalUi {
+    fn log(&mut self, message: &str) {
+        self.log.push(message.to_string());
+        self.log_scroll = 0;
+        let _ = self.render("");
+    }
+
+    fn log_event(&mut self, player: usize, event: Event) {
+        self.log(&get_event_message(player, event));
+    }
+
+    fn show_turn(&mut self, view: &PlayerView) {
+        self.view = Some(view.clone());
+        let _ = self.render("");
+    }
+
+    fn show_board(&mut self, view: &PlayerView) {
+        self.view = Some(view.clone());
+        let _ = self.render("");
+    }
+
+    fn take_action(&mut self, view: &PlayerView) -> Choice {
+        self.view = Some(view.clone());
+        self.selected_card = self.selected_card.min(view.hand.len().saturating_sub(1));
+
+        if self.show_hints {
+            self.hints = advisor::advise(view, advisor::DEFAULT_SAMPLES, &mut rand::thread_rng());
+        }
+
+        self.prompting = true;
+        let choice = self.take_key_action(view).unwrap_or(Choice::Quit);
+        self.prompting = false;
+        choice
+    }
+
+    fn show_result(&mut self, view: &PlayerView, message: &str) {
+        self.pause(view, message);
+    }
+
+    fn show_match_end(&mut self, view: &PlayerView, message: &str) {
+        self.pause(view, message);
+    }
+}
@@ -0,0 +1,36 @@
+use pazaak::{Action, PlayerView};
+
+use crate::messages::Event;
+
+// What a human player chose to do at their turn
+pub enum Choice {
+    Act(Action),
+    // Nothing to apply yet, e.g. a cancelled play, so ask again
+    Nothing,
+    // Save the match to this path and ask again
+    Save(String),
+    Quit,
+}
+
+// How a match is shown to and played by the people at the keyboard. Every view is
+// from the side of the table of whoever is at the keyboard, or whoever last was.
+pub trait Ui {
+    fn log(&mut self, message: &str);
+
+    // Reports a turn event such as a draw or a stand
+    fn log_event(&mut self, player: usize, event: Event);
+
+    // Called at the start of every turn
+    fn show_turn(&mut self, view: &PlayerView);
+
+    // Called whenever a card lands on the table
+    fn show_board(&mut self, view: &PlayerView);
+
+    fn take_action(&mut self, view: &PlayerView) -> Choice;
+
+    // Shows the outcome of a set
+    fn show_result(&mut self, view: &PlayerView, message: &str);
+
+    // Shows the final table and the outcome of the match
+    fn show_match_end(&mut self, view: &PlayerView, message: &str);
+}
@@ -1,7 +1,3 @@
-use core::time;
-use crossterm::style::Stylize;
-use std::{fmt, thread};
-
 use crate::cards::SpecialType;
 
 // Regex for a card with a value
@@ -19,67 +15,29 @@ pub const FLIP_REGEX: &str = r"^([+-]?\d+)/([+-]?\d)$";
 // The cards swap the values on the board corresponding to the values on the card.
 pub const SWAP_REGEX: &str = r"^(\d+)&(\d+)$";
 
+// Regex for a +/-1/2 card which looks like "+/-1/2" or "±1/2"
+// These cards are played as +1, -1, +2 or -2, picked when they are played
+pub const ONE_OR_TWO_REGEX: &str = r"^(?:\+/-|±)1/2$";
+
 // Regex for a Double card which usually looks like "D" capture the D
-// These cards double the value of the board
+// These cards double the value of the last card played
 pub const DOUBLE_REGEX: &str = r"^D$";
 
 // Hashmap of all the special card types and their regex
 pub const SPECIAL_CARD_REGEXES: &[(SpecialType, &str)] = &[
     (SpecialType::TieBreaker, TIEBREAKER_REGEX),
+    (SpecialType::OneOrTwo, ONE_OR_TWO_REGEX),
     (SpecialType::Flip, FLIP_REGEX),
     (SpecialType::Invert, SWAP_REGEX),
     (SpecialType::Double, DOUBLE_REGEX),
     (SpecialType::None, CARD_REGEX),
 ];
 
-pub enum Action {
-    Draw,
-    Stand,
-    EndTurn,
-    Play,
-    TurnStart,
-}
-
-impl fmt::Display for Action {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Action::Draw => write!(f, "Draw"),
-            Action::Stand => write!(f, "Stand"),
-            Action::Play => write!(f, "Play"),
-            Action::TurnStart => write!(f, "Turn Start"),
-            Action::EndTurn => write!(f, "End Turn"),
-        }
-    }
-}
-
-pub fn get_action_message(player: usize, action: Action) -> String {
-    let player_str = format!("Player {}", player + 1);
-    match action {
-        Action::Draw => format!("{} Draws...", player_str),
-        Action::Stand => format!("{} Stands...", player_str),
-        Action::Play => format!("{} Plays...", player_str),
-        Action::TurnStart => format!("Starting {}'s Turn...", player_str),
-        Action::EndTurn => format!("Ending {}'s Turn...", player_str),
+// Reads the number of one of `count` options, which the line interface lists from 1, and
+// returns its index from 0
+pub fn parse_option_number(input: &str, count: usize) -> Option<usize> {
+    match input.trim().parse::<usize>() {
+        Ok(number) if (1..=count).contains(&number) => Some(number - 1),
+        _ => None,
     }
 }
-
-pub fn print_log(message: &str) {
-    println!("{} {}", "~".dark_grey(), message.dark_grey());
-    thread::sleep(time::Duration::from_millis(150));
-}
-
-pub fn print_action_log(player: usize, action: Action) {
-    let message = get_action_message(player, action);
-    print_log(&message);
-    thread::sleep(time::Duration::from_millis(250));
-}
-
-// Show iterable object with indexes
-pub fn print_options<T>(vector: &[T])
-where
-    T: fmt::Display,
-{
-    vector.iter().enumerate().for_each(|(i, object)| {
-        println!("{}: {}", i + 1, object);
-    });
-}
@@ -0,0 +1,103 @@
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{
+    cards::{Board, Card, Match, Status},
+    engine::PlayerId,
+    rules::Rules,
+};
+
+// The parts of a match a single seat is allowed to see. It never holds the
+// opponent's hand or the order of the board deck, only how many cards are left in each.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerView {
+    pub seat: PlayerId,
+    pub hand: Vec<Card>,
+    pub opponent_hand_size: usize,
+    pub board: Board,
+    pub opponent_board: Board,
+    pub status: Status,
+    pub opponent_status: Status,
+    // Whether this seat has already played a card this turn
+    pub played_card: bool,
+    // Points indexed by seat, like `MatchDetails::score`
+    pub score: [u8; 2],
+    pub round: usize,
+    // Number of cards left in the current board deck
+    pub deck_remaining: usize,
+    // The house rules the match is played under
+    pub rules: Rules,
+}
+
+impl PlayerView {
+    pub fn new(pazaak_match: &Match, seat: PlayerId) -> PlayerView {
+        let opponent = 1 - seat;
+        let game = pazaak_match.games.last();
+        let board =
+            |player: PlayerId| game.map_or_else(Board::default, |game| game.board[player].clone());
+
+        PlayerView {
+            seat,
+            hand: pazaak_match.players[seat].hand.cards.clone(),
+            opponent_hand_size: pazaak_match.players[opponent].hand.cards.len(),
+            board: board(seat),
+            opponent_board: board(opponent),
+            status: pazaak_match.players[seat].status,
+            opponent_status: pazaak_match.players[opponent].status,
+            played_card: game.is_some_and(|game| game.played_card),
+            score: pazaak_match.match_detail.score,
+            round: pazaak_match.match_detail.round,
+            deck_remaining: game.map_or(0, |game| game.deck.cards.len()),
+            rules: pazaak_match.rules,
+        }
+    }
+
+    pub fn hand_size(&self) -> usize {
+        self.hand.len()
+    }
+
+    pub fn opponent(&self) -> PlayerId {
+        1 - self.seat
+    }
+
+    // Whether this seat is over 20 but may still play a card to get back under
+    pub fn needs_rescue(&self) -> bool {
+        self.board.total() > 20 && !self.played_card && !self.hand.is_empty()
+    }
+}
+
+impl fmt::Display for PlayerView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hand_string = if self.hand.is_empty() {
+            "<Empty Hand>".yellow().italic().to_string()
+        } else {
+            self.hand
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let opponent_hand_string = if self.opponent_hand_size == 0 {
+            "<Empty Hand>".yellow().italic().to_string()
+        } else {
+            vec!["?"; self.opponent_hand_size].join(", ")
+        };
+
+        // Start with the opponent's information
+        writeln!(f, "{}", "---------------------------".blue().bold())?;
+        writeln!(f, "Opponent Board: {}", self.opponent_board)?;
+        writeln!(f, "Opponent Hand: {}", opponent_hand_string)?;
+
+        // Divider
+        writeln!(f, "{}", "~~~~~~~~~~~~~~~~~~~~~~~~~~~".blue().bold())?;
+
+        // Then, your information
+        writeln!(f, "Your Board: {}", self.board)?;
+        writeln!(f, "Your Hand: {}", hand_string)?;
+
+        // End with a closing line
+        writeln!(f, "{}", "---------------------------".blue().bold())
+    }
+}
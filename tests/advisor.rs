@@ -0,0 +1,135 @@
+use pazaak::{
+    advisor::{self, DeckCounts, Hint, DEFAULT_SAMPLES},
+    engine,
+    solver::Position,
+    Action, PlayerView, Rules,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+fn view(position: &str) -> PlayerView {
+    Position::parse(position)
+        .and_then(|position| position.to_view(Rules::default()))
+        .expect("valid position")
+}
+
+fn advise(view: &PlayerView) -> Vec<Hint> {
+    advisor::advise(view, DEFAULT_SAMPLES, &mut ChaCha12Rng::seed_from_u64(5))
+}
+
+fn assert_close(found: f64, expected: f64) {
+    assert!(
+        (found - expected).abs() < 1e-9,
+        "expected {}, found {}",
+        expected,
+        found
+    );
+}
+
+#[test]
+fn counts_drop_for_cards_drawn_onto_either_board() {
+    let view = view(
+        r#"
+        board = ["10", "9", "+3"]
+        hand = []
+        opponent_board = ["10", "4", "4"]
+        "#,
+    );
+    let counts = DeckCounts::from_view(&view);
+
+    // Side cards never came from the board deck, so the +3 takes nothing away
+    assert_eq!(counts, DeckCounts([4, 4, 4, 2, 4, 4, 4, 4, 3, 2]));
+    assert_eq!(counts.total(), 40 - 5);
+    assert_eq!(view.deck_remaining, counts.total());
+}
+
+#[test]
+fn draw_chances_are_counted_exactly() {
+    // 34 cards are left: two each of 2, 5 and 10, and four of every other value
+    let view = view(
+        r#"
+        board = ["10", "5"]
+        hand = ["+2"]
+        opponent_board = ["10", "2", "5", "2"]
+        opponent_standing = true
+        "#,
+    );
+    let hints = advise(&view);
+    assert_eq!(hints.len(), 2);
+
+    // On 15, a 6 or more busts and a 5 makes 20
+    let stay = &hints[0];
+    assert_eq!((stay.action, stay.total), (None, 15));
+    assert_close(stay.bust_chance, 18.0 / 34.0);
+    assert_close(stay.twenty_chance, 2.0 / 34.0);
+
+    // On 17, a 4 or more busts and a 3 makes 20
+    let play = &hints[1];
+    assert_eq!(
+        play.action,
+        Some(Action::Play {
+            card_index: 0,
+            value_index: 0
+        })
+    );
+    assert_eq!(play.total, 17);
+    assert_close(play.bust_chance, 24.0 / 34.0);
+    assert_close(play.twenty_chance, 4.0 / 34.0);
+}
+
+#[test]
+fn standing_opponents_are_beaten_or_not() {
+    // The opponent stood on 18: standing on 15 loses, and a +4 to 19 wins
+    let hints = advise(&view(
+        r#"
+        board = ["10", "5"]
+        hand = ["+4", "+3"]
+        opponent_board = ["10", "8"]
+        opponent_standing = true
+        "#,
+    ));
+    let chances: Vec<(i8, f64)> = hints
+        .iter()
+        .map(|hint| (hint.total, hint.stand_win_chance))
+        .collect();
+    assert_eq!(chances, [(15, 0.0), (19, 1.0), (18, 0.0)]);
+
+    // A tie only wins with a tiebreaker the opponent doesn't hold
+    let hints = advise(&view(
+        r#"
+        board = ["10", "8"]
+        hand = ["+1/-1T"]
+        opponent_board = ["10", "9"]
+        opponent_standing = true
+        "#,
+    ));
+    let chances: Vec<(i8, f64)> = hints
+        .iter()
+        .map(|hint| (hint.total, hint.stand_win_chance))
+        .collect();
+    assert_eq!(chances, [(18, 0.0), (19, 1.0), (17, 0.0)]);
+}
+
+#[test]
+fn no_card_is_offered_once_one_is_played() {
+    let position = r#"
+        board = ["10", "5"]
+        hand = ["+2", "-1", "+3/-3"]
+        opponent_board = ["10"]
+        played_card = PLAYED
+        "#;
+
+    let playing = view(&position.replace("PLAYED", "false"));
+    let hints = advise(&playing);
+    let plays = engine::card_plays(&playing.hand);
+    assert_eq!(plays.len(), 4);
+    assert_eq!(hints.len(), 1 + plays.len());
+    assert_eq!(hints[0].action, None);
+    for (hint, play) in hints[1..].iter().zip(plays) {
+        assert_eq!(hint.action, Some(play));
+    }
+
+    let hints = advise(&view(&position.replace("PLAYED", "true")));
+    assert_eq!(hints.len(), 1);
+    assert_eq!((hints[0].action, hints[0].total), (None, 15));
+}
@@ -0,0 +1,302 @@
+use pazaak::{
+    deck::{parse_deck, CardKind, DeckViolation, PoolEntry, MAX_CARD_COUNT},
+    Card, Deck, DeckError, DeckFormat, DeckRules,
+};
+
+fn deck_of(cards: &[&str]) -> Deck {
+    Deck {
+        cards: cards
+            .iter()
+            .map(|card| Card::from_string(card).expect("valid card notation"))
+            .collect(),
+    }
+}
+
+fn violations(rules: &DeckRules, cards: &[&str]) -> Vec<DeckViolation> {
+    match rules.validate(&deck_of(cards)) {
+        Ok(()) => vec![],
+        Err(DeckError::Invalid(violations)) => violations,
+        Err(error) => panic!("unexpected deck error: {}", error),
+    }
+}
+
+#[test]
+fn out_of_range_cards_are_unparsable() {
+    for notation in ["+999", "-200", "+1/-999", "999&4", "+999/-1T"] {
+        assert_eq!(Card::from_string(notation), None, "{}", notation);
+    }
+
+    match parse_deck("pazaak-deck 2\n[cards]\n+1\n+999\n") {
+        Err(DeckError::UnparsableLine { line_no, text }) => {
+            assert_eq!((line_no, text.as_str()), (4, "+999"));
+        }
+        other => panic!("expected an unparsable line, got {:?}", other),
+    }
+
+    let json = r#"{ "cards": ["+1", "+999"] }"#;
+    assert!(DeckFormat::Json.parse(json).is_err());
+}
+
+#[test]
+fn legal_decks_pass_the_kotor_rules() {
+    let cards = [
+        "+1", "-2", "+3/-3", "2&4", "3&6", "+/-1/2", "D", "+1/-1T", "6", "-6",
+    ];
+    assert_eq!(violations(&DeckRules::kotor(), &cards), vec![]);
+}
+
+#[test]
+fn every_violation_is_collected() {
+    // Two cards outside the pool, two Doubles over a quota of 1 and one card too many
+    let cards = [
+        "+7", "D", "D", "+1", "+2", "+3", "+4/-4", "9&9", "-1", "-2", "-3",
+    ];
+
+    assert_eq!(
+        violations(&DeckRules::kotor(), &cards),
+        vec![
+            DeckViolation::NotInPool {
+                position: 1,
+                card: "7".to_string(),
+            },
+            DeckViolation::NotInPool {
+                position: 8,
+                card: "9&9".to_string(),
+            },
+            DeckViolation::QuotaExceeded {
+                kind: CardKind::Double,
+                limit: 1,
+                found: 2,
+            },
+            DeckViolation::WrongSize {
+                expected: 10,
+                found: 11,
+            },
+        ]
+    );
+}
+
+#[test]
+fn every_value_has_its_own_kotor_quota() {
+    let quota = |kind| DeckViolation::QuotaExceeded {
+        kind,
+        limit: 2,
+        found: 3,
+    };
+
+    assert_eq!(
+        violations(&DeckRules::kotor(), &["+6"; 10]),
+        vec![DeckViolation::QuotaExceeded {
+            kind: CardKind::Standard { min: 6, max: 6 },
+            limit: 2,
+            found: 10,
+        }]
+    );
+    assert_eq!(
+        violations(
+            &DeckRules::kotor(),
+            &["+1", "+1", "+1", "-2", "-2", "-2", "+3/-3", "+3/-3", "+3/-3", "+4"]
+        ),
+        vec![
+            quota(CardKind::Standard { min: 1, max: 1 }),
+            quota(CardKind::Standard { min: -2, max: -2 }),
+            quota(CardKind::Flip { min: 3, max: 3 }),
+        ]
+    );
+
+    // Two of each of five values is a legal deck
+    assert_eq!(
+        violations(
+            &DeckRules::kotor(),
+            &["+1", "+1", "-1", "-1", "+1/-1", "+1/-1", "+6", "+6", "-6", "-6"]
+        ),
+        vec![]
+    );
+}
+
+#[test]
+fn quotas_and_sizes_follow_the_rules() {
+    let rules = DeckRules {
+        deck_size: 3,
+        pool: vec![
+            PoolEntry {
+                kind: CardKind::Standard { min: 1, max: 6 },
+                limit: 2,
+            },
+            PoolEntry {
+                kind: CardKind::TieBreaker { min: 1, max: 1 },
+                limit: 1,
+            },
+        ],
+    };
+
+    // Exactly at the quota and the size
+    assert_eq!(violations(&rules, &["+1", "+6", "+1/-1T"]), vec![]);
+
+    assert_eq!(
+        violations(&rules, &["+1", "+2", "+3"]),
+        vec![DeckViolation::QuotaExceeded {
+            kind: CardKind::Standard { min: 1, max: 6 },
+            limit: 2,
+            found: 3,
+        }]
+    );
+    assert_eq!(
+        violations(&rules, &["+1", "+1/-1T"]),
+        vec![DeckViolation::WrongSize {
+            expected: 3,
+            found: 2,
+        }]
+    );
+    assert_eq!(
+        violations(&rules, &[]),
+        vec![DeckViolation::WrongSize {
+            expected: 3,
+            found: 0,
+        }]
+    );
+}
+
+fn unparsable_line(content: &str) -> Option<(usize, String)> {
+    match parse_deck(content) {
+        Err(DeckError::UnparsableLine { line_no, text }) => Some((line_no, text)),
+        _ => None,
+    }
+}
+
+#[test]
+fn counted_card_lines_repeat_the_card() {
+    let deck_file =
+        parse_deck("pazaak-deck 2\n[cards]\n2x +2\n1x D\n3x  -1 # spaced out\n+1/-1T\n")
+            .expect("valid deck");
+    assert_eq!(
+        deck_file.deck,
+        deck_of(&["+2", "+2", "D", "-1", "-1", "-1", "+1/-1T"])
+    );
+
+    // Version 1 files take counts too
+    let deck_file = parse_deck("2x 3&6\n+4\n").expect("valid deck");
+    assert_eq!(deck_file.deck, deck_of(&["3&6", "3&6", "+4"]));
+
+    let largest = format!("{}x +1\n", MAX_CARD_COUNT);
+    let deck_file = parse_deck(&largest).expect("valid deck");
+    assert_eq!(deck_file.deck.cards.len(), usize::from(MAX_CARD_COUNT));
+}
+
+#[test]
+fn malformed_counts_are_unparsable() {
+    for line in [
+        "0x +1", "2 +1", "x +1", "-1x +1", "2x", "2x +999", "2x +1 +2",
+    ] {
+        let content = format!("pazaak-deck 2\n[cards]\n{}\n", line);
+        assert_eq!(
+            unparsable_line(&content),
+            Some((3, line.to_string())),
+            "{}",
+            line
+        );
+    }
+}
+
+#[test]
+fn counts_above_a_full_deck_are_unparsable() {
+    // A deck can't hold more copies than this, so the line is refused before any are made
+    assert_eq!(usize::from(MAX_CARD_COUNT), DeckRules::kotor().deck_size);
+
+    for count in [u64::from(MAX_CARD_COUNT) + 1, 4_000_000_000, u64::MAX] {
+        let line = format!("{}x +1", count);
+        assert_eq!(
+            unparsable_line(&format!("{}\n", line)),
+            Some((1, line.clone()))
+        );
+    }
+    assert_eq!(
+        unparsable_line("pazaak-deck 2\n[cards]\n+1\n99999999999999999999999x -1\n"),
+        Some((4, "99999999999999999999999x -1".to_string()))
+    );
+}
+
+// Every kind of card, counted and single, with every optional detail filled in
+const FULL_DECK: &str = "pazaak-deck 2
+name: Balanced
+author: Revan
+
+[cards]
+2x 2
+-3
++1/-1
+3&6
+D
++1/-1T
++/-1/2
+2x -1
+
+[opening]
++1/-1T
+D
+";
+
+#[test]
+fn pzk_decks_survive_json_and_toml() {
+    let original = parse_deck(FULL_DECK).expect("valid deck");
+    // Written .pzk files are already in canonical form
+    assert_eq!(original.to_string(), FULL_DECK);
+
+    for format in [DeckFormat::Json, DeckFormat::Toml] {
+        let written = format.write(&original).expect("deck can be written");
+        let read = format.parse(&written).expect("written deck reads back");
+        assert_eq!(read, original, "{:?}", format);
+
+        let pzk = DeckFormat::Pzk.write(&read).expect("deck can be written");
+        assert_eq!(pzk, FULL_DECK, "{:?}", format);
+    }
+}
+
+#[test]
+fn bare_decks_survive_json() {
+    // A version 1 file, without a name, author or opening
+    let original = parse_deck("+1\n+1\n-2\n+3/-3\n").expect("valid deck");
+
+    let json = DeckFormat::Json
+        .write(&original)
+        .expect("deck can be written");
+    let read = DeckFormat::Json
+        .parse(&json)
+        .expect("written deck reads back");
+    assert_eq!(read, original);
+    assert_eq!(read.name, None);
+    assert!(read.opening.is_empty());
+
+    let pzk = DeckFormat::Pzk.write(&read).expect("deck can be written");
+    assert_eq!(pzk, "pazaak-deck 2\n\n[cards]\n2x 1\n-2\n+3/-3\n");
+    assert_eq!(parse_deck(&pzk).expect("written deck reads back"), original);
+}
+
+#[test]
+fn names_a_pzk_file_would_cut_short_are_rejected() {
+    let json = |name: &str| format!(r#"{{ "name": {:?}, "cards": ["+1", "-2"] }}"#, name);
+
+    for name in ["Revan #1", "Two\nLines", "Carriage\rReturn"] {
+        match DeckFormat::Json.parse(&json(name)) {
+            Err(DeckError::Format(message)) => assert!(message.contains("'#'"), "{}", message),
+            other => panic!("{:?} was read as {:?}", name, other),
+        }
+    }
+    let toml = "author = \"Bastila # Shan\"\ncards = [\"+1\"]\n";
+    assert!(DeckFormat::Toml.parse(toml).is_err());
+
+    // Decks made in code can't be written out either
+    let mut deck_file = parse_deck("+1\n").expect("valid deck");
+    deck_file.author = Some("Revan #1".to_string());
+    assert!(matches!(
+        DeckFormat::Pzk.write(&deck_file),
+        Err(DeckError::UnwritableDetail { key: "author", .. })
+    ));
+
+    // Anything else in a name survives the trip through a .pzk file
+    let read = DeckFormat::Json
+        .parse(&json("Revan: the 1st / \"Prodigal\" Knight"))
+        .expect("valid deck");
+    let pzk = DeckFormat::Pzk.write(&read).expect("deck can be written");
+    assert_eq!(parse_deck(&pzk).expect("written deck reads back"), read);
+}
@@ -0,0 +1,105 @@
+use pazaak::{engine, Board, Card};
+
+// A card in notation and the entry of its values list it is played with
+type Play = (&'static str, usize);
+
+// Builds a board by playing each card onto it in order
+fn board_from(plays: &[Play]) -> Board {
+    let mut board = Board::default();
+    for &(notation, value_index) in plays {
+        play(&mut board, (notation, value_index));
+    }
+    board
+}
+
+fn play(board: &mut Board, (notation, value_index): Play) {
+    let card = Card::from_string(notation).expect("valid card notation");
+    engine::place_card(board, card, value_index);
+}
+
+fn values(board: &Board) -> Vec<i8> {
+    board.cards.iter().map(|card| card.value).collect()
+}
+
+// Board states every special card is played against
+const EMPTY: &[Play] = &[];
+const POSITIVE_LAST: &[Play] = &[("5", 0)];
+const NEGATIVE_LAST: &[Play] = &[("6", 0), ("-4", 0)];
+const FLIP_LAST: &[Play] = &[("4", 0), ("+2/-2", 1)];
+const TIEBREAKER_LAST: &[Play] = &[("7", 0), ("+1/-1T", 0)];
+const INVERT_LAST: &[Play] = &[("4", 0), ("2&4", 0)];
+const DOUBLE_LAST: &[Play] = &[("3", 0), ("D", 0)];
+const MIXED_SIGNS: &[Play] = &[("2", 0), ("-4", 0), ("3", 0)];
+
+// The rule table: board before, card played, board values after
+#[rustfmt::skip]
+const RULES: &[(&str, &[Play], Play, &[i8])] = &[
+    // Standard cards
+    ("standard on empty",       EMPTY,           ("3", 0),      &[3]),
+    ("negative on positive",    POSITIVE_LAST,   ("-3", 0),     &[5, -3]),
+    // Flip cards take whichever value is picked
+    ("flip plus on empty",      EMPTY,           ("+3/-3", 0),  &[3]),
+    ("flip minus on positive",  POSITIVE_LAST,   ("+3/-3", 1),  &[5, -3]),
+    ("flip after flip",         FLIP_LAST,       ("+1/-1", 0),  &[4, -2, 1]),
+    // TieBreakers play like flips
+    ("tiebreaker plus",         POSITIVE_LAST,   ("+1/-1T", 0), &[5, 1]),
+    ("tiebreaker minus",        NEGATIVE_LAST,   ("+1/-1T", 1), &[6, -4, -1]),
+    // +/-1/2 cards play as +1, -1, +2 or -2
+    ("one or two plus one",     EMPTY,           ("+/-1/2", 0), &[1]),
+    ("one or two minus one",    POSITIVE_LAST,   ("±1/2", 1),   &[5, -1]),
+    ("one or two plus two",     NEGATIVE_LAST,   ("+/-1/2", 2), &[6, -4, 2]),
+    ("one or two minus two",    FLIP_LAST,       ("+/-1/2", 3), &[4, -2, -2]),
+    // Invert flips matching cards of either sign and is worth 0 itself
+    ("invert on empty",         EMPTY,           ("2&4", 0),    &[0]),
+    ("invert without matches",  POSITIVE_LAST,   ("2&4", 0),    &[5, 0]),
+    ("invert negative last",    NEGATIVE_LAST,   ("2&4", 0),    &[6, 4, 0]),
+    ("invert mixed signs",      MIXED_SIGNS,     ("2&4", 0),    &[-2, 4, 3, 0]),
+    ("invert flipped flip",     FLIP_LAST,       ("2&4", 0),    &[-4, 2, 0]),
+    ("invert tiebreaker",       TIEBREAKER_LAST, ("1&7", 0),    &[-7, -1, 0]),
+    ("invert double",           DOUBLE_LAST,     ("3&6", 0),    &[-3, -3, 0]),
+    ("invert after invert",     INVERT_LAST,     ("2&4", 0),    &[4, 0, 0]),
+    ("invert one or two",       &[("5", 0), ("+/-1/2", 3)], ("2&4", 0), &[5, 2, 0]),
+    // Double copies the current value of the last card that isn't an Invert
+    ("double on empty",         EMPTY,           ("D", 0),      &[0]),
+    ("double positive last",    POSITIVE_LAST,   ("D", 0),      &[5, 5]),
+    ("double negative last",    NEGATIVE_LAST,   ("D", 0),      &[6, -4, -4]),
+    ("double flip last",        FLIP_LAST,       ("D", 0),      &[4, -2, -2]),
+    ("double tiebreaker last",  TIEBREAKER_LAST, ("D", 0),      &[7, 1, 1]),
+    ("double after invert",     INVERT_LAST,     ("D", 0),      &[-4, 0, -4]),
+    ("double after double",     DOUBLE_LAST,     ("D", 0),      &[3, 3, 3]),
+    ("double mixed signs",      MIXED_SIGNS,     ("D", 0),      &[2, -4, 3, 3]),
+    ("double one or two",       &[("5", 0), ("+/-1/2", 2)], ("D", 0), &[5, 2, 2]),
+];
+
+#[test]
+fn every_special_card_against_every_board_state() {
+    for &(name, before, played, expected) in RULES {
+        let mut board = board_from(before);
+        play(&mut board, played);
+
+        assert_eq!(values(&board), expected, "{}", name);
+    }
+}
+
+#[test]
+fn invert_only_flips_cards_already_on_the_board() {
+    let board = board_from(&[("2", 0), ("2&4", 0), ("2", 0)]);
+
+    assert_eq!(values(&board), [-2, 0, 2]);
+    assert_eq!(board.total(), 0);
+}
+
+#[test]
+fn double_on_a_board_of_only_inverts_is_worth_nothing() {
+    let board = board_from(&[("2&4", 0), ("D", 0)]);
+
+    assert_eq!(values(&board), [0, 0]);
+}
+
+#[test]
+fn doubled_card_is_inverted_with_the_card_it_copied() {
+    let board = board_from(&[("4", 0), ("D", 0), ("2&4", 0)]);
+
+    assert_eq!(values(&board), [-4, -4, 0]);
+    assert_eq!(board.total(), -8);
+}
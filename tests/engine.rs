@@ -0,0 +1,721 @@
+use pazaak::{
+    cards::{ShortDeal, HAND_SIZE},
+    engine::{self, finish_set, start_set, start_turn},
+    Action, Card, Deck, GamePhase, HandPolicy, Match, Outcome, PlayerView, RuleError, Rules,
+    SetOverReason, Status,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+fn cards(notations: &[&str]) -> Vec<Card> {
+    notations
+        .iter()
+        .map(|notation| Card::from_string(notation).expect("valid card notation"))
+        .collect()
+}
+
+// A match with its first set dealt, the given hands and a board deck that deals `draws`
+// in order
+fn new_match(hands: [&[&str]; 2], draws: &[i8]) -> Match {
+    ruled_match(Rules::default(), hands, draws)
+}
+
+fn ruled_match(rules: Rules, hands: [&[&str]; 2], draws: &[i8]) -> Match {
+    let mut pazaak_match = Match::new(Deck::new(), Deck::new(), ChaCha12Rng::seed_from_u64(0));
+    pazaak_match.rules = rules;
+    start_set(&mut pazaak_match).expect("no set has been dealt");
+
+    for (player, hand) in hands.into_iter().enumerate() {
+        pazaak_match.players[player].hand.cards = cards(hand);
+    }
+    // Cards are drawn from the end of the deck
+    pazaak_match.current_game().deck.cards =
+        draws.iter().rev().map(|&value| Card::new(value)).collect();
+    pazaak_match
+}
+
+const PLAY: Action = Action::Play {
+    card_index: 0,
+    value_index: 0,
+};
+
+#[test]
+fn nothing_happens_before_the_first_set() {
+    let mut pazaak_match = Match::new(Deck::new(), Deck::new(), ChaCha12Rng::seed_from_u64(0));
+
+    assert_eq!(
+        start_turn(&mut pazaak_match, 0),
+        Err(RuleError::NoGameInProgress)
+    );
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, Action::Stand),
+        Err(RuleError::NoGameInProgress)
+    );
+    assert_eq!(
+        finish_set(&mut pazaak_match),
+        Err(RuleError::NoGameInProgress)
+    );
+    assert_eq!(pazaak_match.phase, GamePhase::Dealing);
+
+    assert!(start_set(&mut pazaak_match).is_ok());
+    assert_eq!(pazaak_match.match_detail.round, 1);
+    assert_eq!(pazaak_match.phase, GamePhase::Dealing);
+    assert_eq!(pazaak_match.next_player, 0);
+}
+
+#[test]
+fn turns_alternate_in_seat_order() {
+    let mut pazaak_match = new_match([&["+1"], &["+1"]], &[1, 2, 3, 4]);
+
+    // The first player always opens the set
+    assert_eq!(
+        start_turn(&mut pazaak_match, 1),
+        Err(RuleError::NotYourTurn)
+    );
+    assert_eq!(
+        start_turn(&mut pazaak_match, 2),
+        Err(RuleError::InvalidPlayer)
+    );
+    assert_eq!(start_turn(&mut pazaak_match, 0), Ok(Some(Card::new(1))));
+    assert_eq!(pazaak_match.phase, GamePhase::AwaitingAction(0));
+    assert_eq!(pazaak_match.next_player, 1);
+
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, Action::EndTurn),
+        Ok(Outcome::TurnEnded)
+    );
+    assert_eq!(pazaak_match.phase, GamePhase::Dealing);
+
+    // Nobody gets two turns in a row
+    assert_eq!(
+        start_turn(&mut pazaak_match, 0),
+        Err(RuleError::NotYourTurn)
+    );
+    assert_eq!(start_turn(&mut pazaak_match, 1), Ok(Some(Card::new(2))));
+    assert_eq!(pazaak_match.phase, GamePhase::AwaitingAction(1));
+    assert_eq!(pazaak_match.next_player, 0);
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 1, Action::EndTurn),
+        Ok(Outcome::TurnEnded)
+    );
+
+    assert_eq!(
+        start_turn(&mut pazaak_match, 1),
+        Err(RuleError::NotYourTurn)
+    );
+    assert_eq!(start_turn(&mut pazaak_match, 0), Ok(Some(Card::new(3))));
+}
+
+#[test]
+fn turns_in_progress_block_other_calls() {
+    let mut pazaak_match = new_match([&["+1", "+2"], &["+1"]], &[1, 2, 3]);
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+
+    assert_eq!(
+        start_turn(&mut pazaak_match, 1),
+        Err(RuleError::TurnInProgress)
+    );
+    assert_eq!(
+        start_turn(&mut pazaak_match, 0),
+        Err(RuleError::TurnInProgress)
+    );
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 1, Action::Stand),
+        Err(RuleError::NotYourTurn)
+    );
+    assert_eq!(start_set(&mut pazaak_match), Err(RuleError::SetInProgress));
+    assert_eq!(finish_set(&mut pazaak_match), Err(RuleError::SetInProgress));
+
+    // A player may play one card a turn
+    let out_of_range = Action::Play {
+        card_index: 2,
+        value_index: 0,
+    };
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, out_of_range),
+        Err(RuleError::InvalidCardIndex)
+    );
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, PLAY),
+        Ok(Outcome::CardPlayed)
+    );
+    assert_eq!(pazaak_match.phase, GamePhase::AwaitingAction(0));
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, PLAY),
+        Err(RuleError::AlreadyPlayed)
+    );
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, Action::EndTurn),
+        Ok(Outcome::TurnEnded)
+    );
+
+    // Between turns nobody may act
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 1, Action::EndTurn),
+        Err(RuleError::NotYourTurn)
+    );
+}
+
+// Every part of the match, to check that a refused call changed none of it
+fn snapshot(pazaak_match: &Match) -> String {
+    serde_json::to_string(pazaak_match).expect("matches serialize")
+}
+
+#[test]
+fn refused_turns_change_nothing() {
+    let mut pazaak_match = Match::new(Deck::new(), Deck::new(), ChaCha12Rng::seed_from_u64(0));
+    let before = snapshot(&pazaak_match);
+    assert_eq!(
+        start_turn(&mut pazaak_match, 0),
+        Err(RuleError::NoGameInProgress)
+    );
+    assert_eq!(snapshot(&pazaak_match), before);
+
+    let mut pazaak_match = new_match([&["+1"], &["+1"]], &[1, 2, 3, 4]);
+    let before = snapshot(&pazaak_match);
+    for (player, error) in [(1, RuleError::NotYourTurn), (2, RuleError::InvalidPlayer)] {
+        assert_eq!(start_turn(&mut pazaak_match, player), Err(error));
+        assert_eq!(snapshot(&pazaak_match), before);
+    }
+
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    let before = snapshot(&pazaak_match);
+    for player in [0, 1] {
+        assert_eq!(
+            start_turn(&mut pazaak_match, player),
+            Err(RuleError::TurnInProgress)
+        );
+        assert_eq!(snapshot(&pazaak_match), before);
+    }
+
+    engine::apply_action(&mut pazaak_match, 0, Action::Stand).expect("turn in progress");
+    start_turn(&mut pazaak_match, 1).expect("second player's turn");
+    engine::apply_action(&mut pazaak_match, 1, Action::Stand).expect("turn in progress");
+    for finished in [false, true] {
+        if finished {
+            finish_set(&mut pazaak_match).expect("both players stood");
+        }
+        let before = snapshot(&pazaak_match);
+        for player in [0, 1] {
+            assert_eq!(
+                start_turn(&mut pazaak_match, player),
+                Err(RuleError::SetOver)
+            );
+            assert_eq!(snapshot(&pazaak_match), before);
+        }
+    }
+}
+
+#[test]
+fn standing_players_skip_their_turn() {
+    let mut pazaak_match = new_match([&[], &[]], &[1, 2, 3, 4]);
+
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, Action::Stand),
+        Ok(Outcome::Stood)
+    );
+    assert_eq!(pazaak_match.phase, GamePhase::Dealing);
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, Action::EndTurn),
+        Err(RuleError::NotPlaying)
+    );
+
+    start_turn(&mut pazaak_match, 1).expect("second player's turn");
+    engine::apply_action(&mut pazaak_match, 1, Action::EndTurn).expect("turn in progress");
+
+    // The skipped turn still counts, so it is the other player's turn again
+    assert_eq!(start_turn(&mut pazaak_match, 0), Ok(None));
+    assert_eq!(pazaak_match.phase, GamePhase::Dealing);
+    assert_eq!(
+        start_turn(&mut pazaak_match, 0),
+        Err(RuleError::NotYourTurn)
+    );
+    assert_eq!(start_turn(&mut pazaak_match, 1), Ok(Some(Card::new(3))));
+}
+
+#[test]
+fn sets_end_and_the_next_one_starts_with_the_first_player() {
+    let mut pazaak_match = new_match([&[], &[]], &[5, 4, 3, 2]);
+
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    engine::apply_action(&mut pazaak_match, 0, Action::Stand).expect("turn in progress");
+    start_turn(&mut pazaak_match, 1).expect("second player's turn");
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 1, Action::Stand),
+        Ok(Outcome::Stood)
+    );
+    assert_eq!(
+        pazaak_match.phase,
+        GamePhase::SetOver(SetOverReason::BothStanding)
+    );
+    assert!(engine::is_set_over(&pazaak_match));
+
+    // A decided set takes no more turns and has to be finished before the next is dealt
+    assert_eq!(start_turn(&mut pazaak_match, 0), Err(RuleError::SetOver));
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 1, Action::Stand),
+        Err(RuleError::SetOver)
+    );
+    assert_eq!(start_set(&mut pazaak_match), Err(RuleError::SetInProgress));
+
+    assert_eq!(finish_set(&mut pazaak_match), Ok(Some(0)));
+    assert_eq!(pazaak_match.match_detail.score, [1, 0]);
+    assert_eq!(pazaak_match.phase, GamePhase::Dealing);
+    assert_eq!(finish_set(&mut pazaak_match), Err(RuleError::SetInProgress));
+    assert_eq!(start_turn(&mut pazaak_match, 1), Err(RuleError::SetOver));
+
+    start_set(&mut pazaak_match).expect("the last set is finished");
+    assert_eq!(pazaak_match.match_detail.round, 2);
+    assert_eq!(pazaak_match.next_player, 0);
+    assert_eq!(pazaak_match.players[0].status, Status::Playing);
+    assert_eq!(
+        start_turn(&mut pazaak_match, 1),
+        Err(RuleError::NotYourTurn)
+    );
+    assert!(start_turn(&mut pazaak_match, 0).is_ok());
+}
+
+#[test]
+fn an_empty_board_deck_ends_the_set() {
+    let mut pazaak_match = new_match([&[], &[]], &[3]);
+
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    engine::apply_action(&mut pazaak_match, 0, Action::EndTurn).expect("turn in progress");
+
+    // The last card ended the turn with nothing left to draw
+    assert_eq!(
+        pazaak_match.phase,
+        GamePhase::SetOver(SetOverReason::DeckEmpty)
+    );
+    assert_eq!(start_turn(&mut pazaak_match, 1), Err(RuleError::SetOver));
+    assert_eq!(finish_set(&mut pazaak_match), Ok(Some(0)));
+}
+
+#[test]
+fn nothing_happens_after_the_match() {
+    let mut pazaak_match = new_match([&[], &[]], &[9, 1]);
+    pazaak_match.match_detail.score = [2, 0];
+
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    engine::apply_action(&mut pazaak_match, 0, Action::Stand).expect("turn in progress");
+    start_turn(&mut pazaak_match, 1).expect("second player's turn");
+    engine::apply_action(&mut pazaak_match, 1, Action::Stand).expect("turn in progress");
+
+    assert_eq!(finish_set(&mut pazaak_match), Ok(Some(0)));
+    assert_eq!(pazaak_match.phase, GamePhase::MatchOver);
+    assert_eq!(pazaak_match.check_win(), Some(0));
+
+    assert_eq!(start_set(&mut pazaak_match), Err(RuleError::MatchOver));
+    assert_eq!(start_turn(&mut pazaak_match, 0), Err(RuleError::MatchOver));
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, Action::EndTurn),
+        Err(RuleError::MatchOver)
+    );
+    assert_eq!(finish_set(&mut pazaak_match), Err(RuleError::MatchOver));
+}
+
+// Draws for the next `turns` players in turn and ends each turn without playing
+fn pass_turns(pazaak_match: &mut Match, turns: usize) {
+    for _ in 0..turns {
+        let player = pazaak_match.next_player;
+        start_turn(pazaak_match, player).expect("the player's turn");
+        if pazaak_match.phase == GamePhase::AwaitingAction(player) {
+            engine::apply_action(pazaak_match, player, Action::EndTurn).expect("turn in progress");
+        }
+    }
+}
+
+fn total(pazaak_match: &mut Match, player: usize) -> i8 {
+    pazaak_match.current_game().board[player].total()
+}
+
+fn rules(change: impl FnOnce(&mut Rules)) -> Rules {
+    let mut rules = Rules::default();
+    change(&mut rules);
+    rules
+}
+
+// The first player draws 9, 9 and then 5, to 23. The second player stays low.
+const OVER_20: &[i8] = &[9, 1, 9, 1, 5, 1];
+
+#[test]
+fn strict_busts_end_the_turn_when_nothing_can_help() {
+    let mut pazaak_match = new_match([&["+2"], &[]], OVER_20);
+    pass_turns(&mut pazaak_match, 4);
+
+    assert_eq!(start_turn(&mut pazaak_match, 0), Ok(Some(Card::new(5))));
+    assert_eq!(total(&mut pazaak_match, 0), 23);
+    assert_eq!(pazaak_match.players[0].status, Status::Busted);
+    assert_eq!(
+        pazaak_match.phase,
+        GamePhase::SetOver(SetOverReason::Busted(0))
+    );
+    assert_eq!(finish_set(&mut pazaak_match), Ok(Some(1)));
+}
+
+#[test]
+fn strict_busts_offer_a_rescue_card() {
+    let mut pazaak_match = new_match([&["+2", "+4/-4"], &[]], OVER_20);
+    pass_turns(&mut pazaak_match, 4);
+
+    // The flip card can still bring the total back, so the player is asked what to do
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    assert_eq!(pazaak_match.phase, GamePhase::AwaitingAction(0));
+    assert_eq!(pazaak_match.players[0].status, Status::Playing);
+
+    let rescue = Action::Play {
+        card_index: 1,
+        value_index: 1,
+    };
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, rescue),
+        Ok(Outcome::CardPlayed)
+    );
+    assert_eq!(total(&mut pazaak_match, 0), 19);
+    assert_eq!(pazaak_match.players[0].status, Status::Playing);
+}
+
+#[test]
+fn strict_busts_bust_a_player_who_declines_the_rescue() {
+    for action in [Action::Stand, Action::EndTurn, PLAY] {
+        let mut pazaak_match = new_match([&["+2", "-4"], &[]], OVER_20);
+        pass_turns(&mut pazaak_match, 4);
+        start_turn(&mut pazaak_match, 0).expect("first player's turn");
+
+        // Playing the wrong card uses up the turn's only play
+        assert_eq!(
+            engine::apply_action(&mut pazaak_match, 0, action),
+            Ok(Outcome::Busted),
+            "{:?}",
+            action
+        );
+        assert_eq!(pazaak_match.players[0].status, Status::Busted);
+        assert_eq!(
+            pazaak_match.phase,
+            GamePhase::SetOver(SetOverReason::Busted(0))
+        );
+    }
+}
+
+#[test]
+fn loose_busts_only_bust_at_the_end_of_the_turn() {
+    let loose = rules(|rules| rules.strict_busts = false);
+
+    // Nothing in hand could help, but the turn goes on
+    let mut pazaak_match = ruled_match(loose, [&["+2"], &[]], OVER_20);
+    pass_turns(&mut pazaak_match, 4);
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    assert_eq!(pazaak_match.phase, GamePhase::AwaitingAction(0));
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, PLAY),
+        Ok(Outcome::CardPlayed)
+    );
+    assert_eq!(total(&mut pazaak_match, 0), 25);
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, Action::EndTurn),
+        Ok(Outcome::Busted)
+    );
+    assert_eq!(
+        pazaak_match.phase,
+        GamePhase::SetOver(SetOverReason::Busted(0))
+    );
+
+    // Standing over 20 isn't a bust, though it still loses the set
+    let mut pazaak_match = ruled_match(loose, [&[], &[]], OVER_20);
+    pass_turns(&mut pazaak_match, 4);
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, Action::Stand),
+        Ok(Outcome::Stood)
+    );
+    assert_eq!(pazaak_match.players[0].status, Status::Standing);
+
+    start_turn(&mut pazaak_match, 1).expect("second player's turn");
+    engine::apply_action(&mut pazaak_match, 1, Action::Stand).expect("turn in progress");
+    assert_eq!(finish_set(&mut pazaak_match), Ok(Some(1)));
+}
+
+// The first player draws 9, 9 and then 2, to exactly 20
+const TO_20: &[i8] = &[9, 1, 9, 1, 2, 1];
+
+#[test]
+fn auto_stand_stands_a_player_on_20() {
+    // Drawn to 20
+    let mut pazaak_match = new_match([&[], &[]], TO_20);
+    pass_turns(&mut pazaak_match, 4);
+    assert_eq!(start_turn(&mut pazaak_match, 0), Ok(Some(Card::new(2))));
+    assert_eq!(pazaak_match.players[0].status, Status::Standing);
+    assert_eq!(pazaak_match.phase, GamePhase::Dealing);
+    assert_eq!(start_turn(&mut pazaak_match, 1), Ok(Some(Card::new(1))));
+
+    // Played to 20
+    let mut pazaak_match = new_match([&["+2"], &[]], OVER_20);
+    pass_turns(&mut pazaak_match, 2);
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, PLAY),
+        Ok(Outcome::Stood)
+    );
+    assert_eq!(pazaak_match.players[0].status, Status::Standing);
+}
+
+#[test]
+fn without_auto_stand_20_is_played_on() {
+    let manual = rules(|rules| rules.auto_stand = false);
+
+    let mut pazaak_match = ruled_match(manual, [&[], &[]], TO_20);
+    pass_turns(&mut pazaak_match, 4);
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    assert_eq!(pazaak_match.phase, GamePhase::AwaitingAction(0));
+    assert_eq!(pazaak_match.players[0].status, Status::Playing);
+
+    let mut pazaak_match = ruled_match(manual, [&["+2"], &[]], OVER_20);
+    pass_turns(&mut pazaak_match, 2);
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 0, PLAY),
+        Ok(Outcome::CardPlayed)
+    );
+    assert_eq!(total(&mut pazaak_match, 0), 20);
+    assert_eq!(pazaak_match.phase, GamePhase::AwaitingAction(0));
+}
+
+// Enough ones for both players to fill the table
+const ONES: &[i8] = &[1; 20];
+
+#[test]
+fn filling_the_table_wins_the_set() {
+    // By drawing the ninth card
+    let mut pazaak_match = new_match([&[], &[]], ONES);
+    pass_turns(&mut pazaak_match, 16);
+    assert_eq!(start_turn(&mut pazaak_match, 0), Ok(Some(Card::new(1))));
+    assert_eq!(
+        pazaak_match.phase,
+        GamePhase::SetOver(SetOverReason::FilledTable(0))
+    );
+    assert_eq!(finish_set(&mut pazaak_match), Ok(Some(0)));
+
+    // By playing it
+    let mut pazaak_match = new_match([&[], &["-1"]], ONES);
+    pass_turns(&mut pazaak_match, 15);
+    start_turn(&mut pazaak_match, 1).expect("second player's turn");
+    assert_eq!(
+        engine::apply_action(&mut pazaak_match, 1, PLAY),
+        Ok(Outcome::FilledTable)
+    );
+    assert_eq!(
+        pazaak_match.phase,
+        GamePhase::SetOver(SetOverReason::FilledTable(1))
+    );
+    assert_eq!(finish_set(&mut pazaak_match), Ok(Some(1)));
+}
+
+#[test]
+fn without_fill_the_table_full_boards_play_on() {
+    let unlimited = rules(|rules| rules.fill_the_table = false);
+
+    let mut pazaak_match = ruled_match(unlimited, [&[], &[]], ONES);
+    pass_turns(&mut pazaak_match, 16);
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    assert_eq!(pazaak_match.phase, GamePhase::AwaitingAction(0));
+    assert_eq!(engine::filled_table(&pazaak_match), None);
+    engine::apply_action(&mut pazaak_match, 0, Action::Stand).expect("turn in progress");
+
+    start_turn(&mut pazaak_match, 1).expect("second player's turn");
+    assert_eq!(pazaak_match.phase, GamePhase::AwaitingAction(1));
+    engine::apply_action(&mut pazaak_match, 1, Action::EndTurn).expect("turn in progress");
+    assert_eq!(start_turn(&mut pazaak_match, 0), Ok(None));
+
+    // A tenth card is just another card, and the higher total wins
+    start_turn(&mut pazaak_match, 1).expect("second player's turn");
+    assert_eq!(pazaak_match.current_game().board[1].cards.len(), 10);
+    engine::apply_action(&mut pazaak_match, 1, Action::Stand).expect("turn in progress");
+    assert_eq!(
+        pazaak_match.phase,
+        GamePhase::SetOver(SetOverReason::BothStanding)
+    );
+    assert_eq!(finish_set(&mut pazaak_match), Ok(Some(1)));
+}
+
+#[test]
+fn legal_actions_cover_every_way_to_play() {
+    let hand = cards(&["+3", "+1/-1", "+/-1/2", "D"]);
+    let play = |card_index, value_index| Action::Play {
+        card_index,
+        value_index,
+    };
+
+    assert_eq!(
+        engine::card_plays(&hand),
+        [
+            play(0, 0),
+            play(1, 0),
+            play(1, 1),
+            play(2, 0),
+            play(2, 1),
+            play(2, 2),
+            play(2, 3),
+            play(3, 0),
+        ]
+    );
+
+    let mut pazaak_match = new_match([&["+3", "+1/-1"], &[]], &[4, 5]);
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    let view = PlayerView::new(&pazaak_match, 0);
+    assert_eq!(
+        engine::legal_actions(&view),
+        [
+            Action::Stand,
+            Action::EndTurn,
+            play(0, 0),
+            play(1, 0),
+            play(1, 1)
+        ]
+    );
+
+    // Every legal action is accepted, and nothing more is once a card is played
+    for action in engine::legal_actions(&view) {
+        let mut pazaak_match = pazaak_match.clone();
+        assert!(engine::apply_action(&mut pazaak_match, 0, action).is_ok());
+    }
+    engine::apply_action(&mut pazaak_match, 0, play(1, 1)).expect("turn in progress");
+    let view = PlayerView::new(&pazaak_match, 0);
+    assert_eq!(
+        engine::legal_actions(&view),
+        [Action::Stand, Action::EndTurn]
+    );
+
+    // The board a play leads to is the one the engine makes
+    assert_eq!(
+        engine::board_after(&view.board, &view.hand, Action::Stand).total(),
+        3
+    );
+    assert_eq!(
+        engine::board_after(&view.board, &view.hand, play(0, 0)).total(),
+        6
+    );
+}
+
+// A match that deals its hands from the given side decks, from the last card back
+fn dealt_match(hand_policy: HandPolicy, side_decks: [&[&str]; 2]) -> Match {
+    let [deck1, deck2] = side_decks.map(|side_deck| Deck {
+        cards: cards(side_deck),
+    });
+    let mut pazaak_match = Match::new(deck1, deck2, ChaCha12Rng::seed_from_u64(0));
+    pazaak_match.rules.hand_policy = hand_policy;
+    pazaak_match
+}
+
+// Both players stand on their first card, then the set is finished
+fn stand_out_the_set(pazaak_match: &mut Match) {
+    for player in [0, 1] {
+        start_turn(pazaak_match, player).expect("the player's turn");
+        if pazaak_match.phase == GamePhase::AwaitingAction(player) {
+            engine::apply_action(pazaak_match, player, Action::Stand).expect("turn in progress");
+        }
+    }
+    finish_set(pazaak_match).expect("both players stood");
+}
+
+const SIDE_DECK: &[&str] = &["+1", "+2", "+3", "+4", "+5", "+6", "-1", "-2", "-3", "-4"];
+
+#[test]
+fn kept_hands_last_the_whole_match() {
+    let mut pazaak_match = dealt_match(HandPolicy::KeepForMatch, [SIDE_DECK, SIDE_DECK]);
+
+    assert_eq!(start_set(&mut pazaak_match), Ok(vec![]));
+    let hand = cards(&["-4", "-3", "-2", "-1"]);
+    assert_eq!(pazaak_match.players[0].hand.cards, hand);
+    assert_eq!(pazaak_match.players[1].hand.cards, hand);
+
+    // A card played in the first set is gone for good
+    start_turn(&mut pazaak_match, 0).expect("first player's turn");
+    engine::apply_action(&mut pazaak_match, 0, PLAY).expect("the card is in hand");
+    engine::apply_action(&mut pazaak_match, 0, Action::Stand).expect("turn in progress");
+    start_turn(&mut pazaak_match, 1).expect("second player's turn");
+    engine::apply_action(&mut pazaak_match, 1, Action::Stand).expect("turn in progress");
+    finish_set(&mut pazaak_match).expect("both players stood");
+
+    // Two more sets, so that nobody can have won the match yet
+    for _ in 0..2 {
+        assert_eq!(start_set(&mut pazaak_match), Ok(vec![]));
+        assert_eq!(pazaak_match.players[0].hand.cards, hand[1..]);
+        assert_eq!(pazaak_match.players[1].hand.cards, hand);
+        assert_eq!(pazaak_match.players[0].deck.cards.len(), 6);
+        stand_out_the_set(&mut pazaak_match);
+    }
+}
+
+#[test]
+fn dealing_each_set_deals_a_fresh_hand() {
+    let mut pazaak_match = dealt_match(HandPolicy::DealEachSet, [SIDE_DECK, SIDE_DECK]);
+
+    assert_eq!(start_set(&mut pazaak_match), Ok(vec![]));
+    assert_eq!(
+        pazaak_match.players[0].hand.cards,
+        cards(&["-4", "-3", "-2", "-1"])
+    );
+    stand_out_the_set(&mut pazaak_match);
+
+    // The unplayed hand is thrown away for the next 4 cards of the side deck
+    assert_eq!(start_set(&mut pazaak_match), Ok(vec![]));
+    for player in [0, 1] {
+        assert_eq!(
+            pazaak_match.players[player].hand.cards,
+            cards(&["+6", "+5", "+4", "+3"])
+        );
+        assert_eq!(pazaak_match.players[player].deck.cards.len(), 2);
+    }
+}
+
+#[test]
+fn short_side_decks_are_reported_for_their_seat() {
+    let short_deck: &[&str] = &["+1", "+2", "+3", "+4", "+5", "+6"];
+    let mut pazaak_match = dealt_match(HandPolicy::DealEachSet, [short_deck, SIDE_DECK]);
+
+    assert_eq!(start_set(&mut pazaak_match), Ok(vec![]));
+    stand_out_the_set(&mut pazaak_match);
+
+    assert_eq!(
+        start_set(&mut pazaak_match),
+        Ok(vec![ShortDeal {
+            player: 0,
+            dealt: 2,
+            wanted: HAND_SIZE,
+        }])
+    );
+    assert_eq!(pazaak_match.players[0].hand.cards, cards(&["+2", "+1"]));
+    assert_eq!(pazaak_match.players[1].hand.cards.len(), HAND_SIZE);
+    stand_out_the_set(&mut pazaak_match);
+
+    assert_eq!(
+        start_set(&mut pazaak_match),
+        Ok(vec![
+            ShortDeal {
+                player: 0,
+                dealt: 0,
+                wanted: HAND_SIZE,
+            },
+            ShortDeal {
+                player: 1,
+                dealt: 2,
+                wanted: HAND_SIZE,
+            },
+        ])
+    );
+    assert!(pazaak_match.players[0].hand.cards.is_empty());
+
+    // Hands kept for the match are only dealt once, so they never run short later
+    let mut pazaak_match = dealt_match(HandPolicy::KeepForMatch, [&["+1", "+2"], SIDE_DECK]);
+    assert_eq!(
+        start_set(&mut pazaak_match),
+        Ok(vec![ShortDeal {
+            player: 0,
+            dealt: 2,
+            wanted: HAND_SIZE,
+        }])
+    );
+    stand_out_the_set(&mut pazaak_match);
+    assert_eq!(start_set(&mut pazaak_match), Ok(vec![]));
+}
@@ -0,0 +1,142 @@
+use pazaak::{
+    agent::BasicAgent,
+    deck::parse_deck,
+    engine,
+    env::{self, EnvError, Observation, PazaakEnv, ACTION_COUNT, OBSERVATION_SIZE},
+    Action, Agent, PlayerView, RuleError,
+};
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use std::cell::RefCell;
+
+const DECK: &str = "+2\n-3\n+1/-1\n4\n+1/-1T\n-1\n+/-1/2\n2&4\nD\n-2\n";
+
+fn new_env() -> PazaakEnv {
+    let deck = parse_deck(DECK).expect("valid deck");
+    PazaakEnv::new([deck.clone(), deck], Box::new(BasicAgent::default()))
+}
+
+#[test]
+fn action_ids_round_trip() {
+    for id in 0..ACTION_COUNT {
+        let action = env::action_from_id(id).expect("id in range");
+        assert_eq!(env::action_id(action), Some(id));
+    }
+    assert_eq!(env::action_from_id(ACTION_COUNT), None);
+    assert_eq!(env::action_id(Action::Stand), Some(0));
+}
+
+#[test]
+fn random_legal_play_finishes_matches() {
+    let mut env = new_env();
+    let mut rng = ChaCha12Rng::seed_from_u64(7);
+
+    for seed in 0..20 {
+        env.seat = (seed % 2) as usize;
+        let mut observation = env.reset(seed).expect("the seat is valid");
+        let mut reward = 0.0;
+        let mut done = false;
+
+        while !done {
+            assert_eq!(observation.len(), OBSERVATION_SIZE);
+
+            let legal: Vec<usize> = (0..ACTION_COUNT)
+                .filter(|&id| env.legal_actions()[id])
+                .collect();
+            let id = *legal
+                .choose(&mut rng)
+                .expect("an action is legal until the end");
+            (observation, reward, done) = env.step(id).expect("legal actions are accepted");
+        }
+
+        assert!([-1.0, 0.0, 1.0].contains(&reward));
+        assert!(env.legal_actions().iter().all(|&legal| !legal));
+        assert!(env.step(0).is_err());
+    }
+}
+
+#[test]
+fn masked_actions_are_rejected() {
+    let mut env = new_env();
+    env.reset(1).expect("the seat is valid");
+
+    let mask = env.legal_actions();
+    let illegal = (0..ACTION_COUNT)
+        .find(|&id| !mask[id])
+        .expect("some hand value index is unused");
+    assert_eq!(
+        env.step(illegal),
+        Err(env::EnvError::IllegalAction(illegal))
+    );
+    assert_eq!(
+        env.step(ACTION_COUNT),
+        Err(env::EnvError::IllegalAction(ACTION_COUNT))
+    );
+}
+
+// Picks any legal action at random
+struct RandomAgent {
+    rng: RefCell<ChaCha12Rng>,
+}
+
+impl RandomAgent {
+    fn new(seed: u64) -> RandomAgent {
+        RandomAgent {
+            rng: RefCell::new(ChaCha12Rng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl Agent for RandomAgent {
+    fn name(&self) -> &str {
+        "test:random"
+    }
+
+    fn choose_action(&self, view: &PlayerView) -> Action {
+        *engine::legal_actions(view)
+            .choose(&mut *self.rng.borrow_mut())
+            .expect("standing is always legal")
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        *self.rng.get_mut() = ChaCha12Rng::seed_from_u64(seed);
+    }
+}
+
+// Every observation of an episode where the learner always takes its first legal action
+fn episode(env: &mut PazaakEnv, seed: u64) -> Vec<Observation> {
+    let mut observations = vec![env.reset(seed).expect("the seat is valid")];
+    while !env.is_done() {
+        let id = env
+            .legal_actions()
+            .iter()
+            .position(|&legal| legal)
+            .expect("an action is legal until the end");
+        let (observation, _, _) = env.step(id).expect("legal actions are accepted");
+        observations.push(observation);
+    }
+    observations
+}
+
+#[test]
+fn the_seed_replays_a_random_opponent() {
+    let deck = parse_deck(DECK).expect("valid deck");
+    // However their randomness started, the opponents are reseeded by every reset
+    let mut env1 = PazaakEnv::new([deck.clone(), deck.clone()], Box::new(RandomAgent::new(1)));
+    let mut env2 = PazaakEnv::new([deck.clone(), deck], Box::new(RandomAgent::new(2)));
+
+    for seed in 0..5 {
+        let first = episode(&mut env1, seed);
+        assert_eq!(first, episode(&mut env2, seed));
+        assert_eq!(first, episode(&mut env1, seed));
+    }
+}
+
+#[test]
+fn rule_errors_are_returned() {
+    let mut env = new_env();
+    env.seat = 2;
+    assert_eq!(env.reset(0), Err(EnvError::Rule(RuleError::InvalidPlayer)));
+    assert!(env.is_done());
+    assert_eq!(env.step(0), Err(EnvError::NotRunning));
+}
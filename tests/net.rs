@@ -0,0 +1,84 @@
+use pazaak::{
+    net::{
+        self, ClientMessage, Connection, HostMessage, NetError, MAX_MESSAGE_SIZE, PROTOCOL_VERSION,
+        REMOTE_SEAT,
+    },
+    DeckRules,
+};
+use std::{
+    io::Write,
+    net::{TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+const DECK: [&str; 10] = ["+1", "+2", "+3", "+4", "-1", "-2", "-3", "-4", "+1/-1", "D"];
+
+fn hello(deck: &[&str]) -> ClientMessage {
+    ClientMessage::Hello {
+        version: PROTOCOL_VERSION,
+        deck: deck.iter().map(|card| card.to_string()).collect(),
+        opening: vec![],
+    }
+}
+
+// Has a joiner say `message` to a host accepting on a local port, returning what the host
+// made of it and the host's answer
+fn handshake(message: ClientMessage) -> (Result<(), NetError>, HostMessage) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("local port");
+    let address = listener.local_addr().expect("bound address");
+
+    let joiner = thread::spawn(move || {
+        let mut connection =
+            Connection::new(TcpStream::connect(address).expect("host is up")).expect("connection");
+        connection.send(&message).expect("host is reading");
+        connection
+            .receive::<HostMessage>(Some(Duration::from_secs(5)))
+            .expect("host answers")
+    });
+
+    let result = net::accept_player(&listener, &DeckRules::kotor()).map(|_| ());
+    (result, joiner.join().expect("joiner thread"))
+}
+
+#[test]
+fn legal_decks_are_welcomed() {
+    let (result, answer) = handshake(hello(&DECK));
+
+    assert!(result.is_ok());
+    assert!(matches!(answer, HostMessage::Welcome { version, seat }
+        if version == PROTOCOL_VERSION && seat == REMOTE_SEAT));
+}
+
+#[test]
+fn out_of_range_cards_are_rejected() {
+    let mut deck = DECK;
+    deck[0] = "+999";
+    let (result, answer) = handshake(hello(&deck));
+
+    assert!(matches!(result, Err(NetError::Rejected(reason)) if reason.contains("+999")));
+    assert!(matches!(answer, HostMessage::Rejected(reason) if reason.contains("+999")));
+
+    let (result, _) = handshake(ClientMessage::Hello {
+        version: PROTOCOL_VERSION,
+        deck: DECK.iter().map(|card| card.to_string()).collect(),
+        opening: vec!["-999".to_string()],
+    });
+    assert!(matches!(result, Err(NetError::Rejected(reason)) if reason.contains("-999")));
+}
+
+#[test]
+fn endless_lines_are_cut_off() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("local port");
+    let address = listener.local_addr().expect("bound address");
+
+    let joiner = thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).expect("host is up");
+        // The host hangs up part way through, so later writes may fail
+        let _ = stream.write_all(&vec![b' '; MAX_MESSAGE_SIZE * 4]);
+    });
+
+    let result = net::accept_player(&listener, &DeckRules::kotor());
+    assert!(matches!(result, Err(NetError::MessageTooLong)));
+    joiner.join().expect("joiner thread");
+}
@@ -0,0 +1,177 @@
+use pazaak::ratings::{RatedMatch, Rating, RatingsStore};
+
+fn rating(rating: f64, deviation: f64) -> Rating {
+    Rating {
+        rating,
+        deviation,
+        ..Rating::default()
+    }
+}
+
+fn assert_close(found: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (found - expected).abs() < tolerance,
+        "expected {}, found {}",
+        expected,
+        found
+    );
+}
+
+#[test]
+fn glickmans_example_is_reproduced() {
+    // The worked example in Glickman's "Example of the Glicko-2 system": a 1500 player
+    // beats a 1400 player, then loses to a 1550 and a 1700 player in one rating period
+    let player = rating(1500.0, 200.0);
+    let updated = player.update_period(&[
+        (rating(1400.0, 30.0), 1.0),
+        (rating(1550.0, 100.0), 0.0),
+        (rating(1700.0, 300.0), 0.0),
+    ]);
+
+    assert_close(updated.rating, 1464.06, 0.01);
+    assert_close(updated.deviation, 151.52, 0.01);
+    assert_close(updated.volatility, 0.05999, 0.00001);
+}
+
+#[test]
+fn single_games_are_periods_of_their_own() {
+    let player = rating(1500.0, 200.0);
+    let opponent = rating(1400.0, 30.0);
+
+    assert_eq!(
+        player.update(&opponent, 1.0),
+        player.update_period(&[(opponent, 1.0)])
+    );
+
+    let won = player.update(&opponent, 1.0);
+    let drawn = player.update(&opponent, 0.5);
+    let lost = player.update(&opponent, 0.0);
+    assert!(won.rating > drawn.rating && drawn.rating > lost.rating);
+    // Drawing with a weaker player costs rating
+    assert!(drawn.rating < player.rating);
+    for updated in [won, drawn, lost] {
+        assert!(updated.deviation < player.deviation);
+    }
+}
+
+#[test]
+fn idle_periods_only_add_uncertainty() {
+    let player = rating(1700.0, 50.0);
+    let idle = player.update_period(&[]);
+
+    assert_eq!(idle.rating, player.rating);
+    assert_eq!(idle.volatility, player.volatility);
+    assert!(idle.deviation > player.deviation);
+}
+
+#[test]
+fn matches_are_recorded_for_both_players() {
+    let mut store = RatingsStore::default();
+
+    assert!(store.record_match(["Revan", "ai:basic"], Some(0)));
+    assert!(store.record_match(["ai:basic", "Revan"], None));
+
+    let revan = &store.players["Revan"];
+    let agent = &store.players["ai:basic"];
+    assert_eq!((revan.wins(), revan.losses()), (1, 0));
+    assert_eq!((agent.wins(), agent.losses()), (0, 1));
+    assert!(revan.rating.rating > agent.rating.rating);
+    assert_eq!(revan.history[1].game, 2);
+    assert_eq!(revan.history[1].opponent, "ai:basic");
+}
+
+#[test]
+fn players_are_never_rated_against_themselves() {
+    let mut store = RatingsStore::default();
+
+    assert!(!store.record_match(["ai:basic", "ai:basic"], Some(0)));
+    assert_eq!(store, RatingsStore::default());
+}
+
+#[test]
+fn matches_with_an_id_are_rated_once() {
+    let mut store = RatingsStore::default();
+
+    assert!(store.record_match_once("round 1", ["Revan", "Bastila"], Some(1)));
+    let rated = store.clone();
+    assert!(!store.record_match_once("round 1", ["Revan", "Bastila"], Some(1)));
+    assert_eq!(store, rated);
+
+    assert!(store.record_match_once("round 2", ["Revan", "Bastila"], Some(1)));
+    assert_eq!(store.games, 2);
+
+    // A match nobody could be rated for doesn't use up its id
+    assert!(!store.record_match_once("round 3", ["Revan", "Revan"], None));
+    assert!(!store.rated.contains("round 3"));
+}
+
+fn rated_match<'a>(
+    id: Option<&str>,
+    players: [&'a str; 2],
+    winner: Option<usize>,
+) -> RatedMatch<'a> {
+    RatedMatch {
+        id: id.map(str::to_string),
+        players,
+        winner,
+    }
+}
+
+#[test]
+fn periods_rate_everyone_once_from_where_they_started() {
+    let mut store = RatingsStore::default();
+    assert!(store.record_match(["Carth", "Mission"], Some(0)));
+    let carth = store.players["Carth"].rating;
+    let mission = store.players["Mission"].rating;
+
+    let rated = store.record_period(&[
+        rated_match(None, ["Revan", "Bastila"], Some(0)),
+        rated_match(None, ["Bastila", "Carth"], None),
+        rated_match(None, ["Revan", "Carth"], Some(1)),
+    ]);
+    assert_eq!(rated, 3);
+    assert_eq!(store.games, 4);
+
+    // Later matches in the period are rated against where the players started it
+    let new = Rating::default();
+    let revan = new.update_period(&[(new, 1.0), (carth, 0.0)]);
+    let bastila = new.update_period(&[(new, 0.0), (carth, 0.5)]);
+    let updated_carth = carth.update_period(&[(new, 0.5), (new, 1.0)]);
+    assert_eq!(store.players["Revan"].rating, revan);
+    assert_eq!(store.players["Bastila"].rating, bastila);
+    assert_eq!(store.players["Carth"].rating, updated_carth);
+
+    // Every match of the period is in the history with the rating the period ended on
+    let history = &store.players["Revan"].history;
+    assert_eq!(history.len(), 2);
+    assert_eq!((history[0].game, history[1].game), (2, 4));
+    assert!(history.iter().all(|entry| entry.rating == revan));
+
+    // Sitting the period out only made Mission less certain
+    assert_eq!(store.players["Mission"].rating, mission.update_period(&[]));
+    assert_eq!(store.players["Mission"].history.len(), 1);
+}
+
+#[test]
+fn periods_skip_matches_that_cant_be_rated() {
+    let mut store = RatingsStore::default();
+    assert!(store.record_match_once("round 1", ["Revan", "Bastila"], Some(0)));
+    let before = store.clone();
+
+    // Nothing left to rate is no period at all, so nobody grows less certain
+    let unrated = [
+        rated_match(Some("round 1"), ["Revan", "Bastila"], Some(0)),
+        rated_match(None, ["Revan", "Revan"], None),
+    ];
+    assert_eq!(store.record_period(&unrated), 0);
+    assert_eq!(store, before);
+
+    let rated = store.record_period(&[
+        rated_match(Some("round 2"), ["Revan", "Bastila"], Some(1)),
+        rated_match(Some("round 2"), ["Revan", "Bastila"], Some(1)),
+        rated_match(Some("round 1"), ["Revan", "Bastila"], Some(0)),
+    ]);
+    assert_eq!(rated, 1);
+    assert_eq!(store.games, 2);
+    assert!(store.rated.contains("round 2"));
+}
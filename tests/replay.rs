@@ -0,0 +1,152 @@
+use pazaak::{
+    agent::BasicAgent,
+    deck::parse_deck,
+    engine::{self, GamePhase},
+    replay::{self, ReplayError, ReplayEvent, Replayer, REPLAY_VERSION},
+    Action, Agent, Match, PlayerView,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::io::BufReader;
+
+const DECK: &str = "+2\n-3\n+1/-1\n4\n+1/-1T\n-1\n+/-1/2\n2&4\nD\n-2\n";
+
+// Plays a match between two basic agents, recording it the way a session does
+fn record_match(seed: u64) -> (Match, Vec<ReplayEvent>) {
+    let deck = parse_deck(DECK).expect("valid deck");
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    let decks = [deck.shuffled(&mut rng), deck.shuffled(&mut rng)];
+    let mut pazaak_match = Match::new(decks[0].clone(), decks[1].clone(), rng);
+    let agent = BasicAgent::default();
+
+    let mut events = vec![
+        ReplayEvent::Header {
+            version: REPLAY_VERSION,
+        },
+        ReplayEvent::Seed(seed),
+    ];
+    let status_events = |pazaak_match: &Match, before: [_; 2], events: &mut Vec<ReplayEvent>| {
+        for (player, before) in before.into_iter().enumerate() {
+            let status = pazaak_match.players[player].status;
+            if status != before {
+                events.push(ReplayEvent::Status { player, status });
+            }
+        }
+    };
+
+    while pazaak_match.check_win().is_none() {
+        engine::start_set(&mut pazaak_match).expect("the last set is finished");
+        let round = pazaak_match.match_detail.round;
+        events.push(ReplayEvent::SetStart { round });
+        for player in 0..2 {
+            let cards = pazaak_match.players[player].hand.cards.clone();
+            events.push(ReplayEvent::Hand { player, cards });
+        }
+
+        'set: loop {
+            for player in 0..2 {
+                if engine::is_set_over(&pazaak_match) {
+                    break 'set;
+                }
+
+                let before = pazaak_match.players.each_ref().map(|player| player.status);
+                match engine::start_turn(&mut pazaak_match, player) {
+                    Ok(Some(card)) => events.push(ReplayEvent::Draw { player, card }),
+                    Ok(None) => continue,
+                    Err(_) => break 'set,
+                }
+                status_events(&pazaak_match, before, &mut events);
+
+                while pazaak_match.phase == GamePhase::AwaitingAction(player) {
+                    let action = agent.choose_action(&PlayerView::new(&pazaak_match, player));
+                    let before = pazaak_match.players.each_ref().map(|player| player.status);
+                    let event = match action {
+                        Action::Play {
+                            card_index,
+                            value_index,
+                        } => ReplayEvent::Play {
+                            player,
+                            card_index,
+                            value_index,
+                            card: pazaak_match.players[player].hand.cards[card_index].clone(),
+                        },
+                        Action::Stand => ReplayEvent::Stand { player },
+                        Action::EndTurn => ReplayEvent::EndTurn { player },
+                    };
+
+                    engine::apply_action(&mut pazaak_match, player, action)
+                        .expect("basic agents play legal actions");
+                    events.push(event);
+                    status_events(&pazaak_match, before, &mut events);
+                }
+            }
+        }
+
+        let winner = engine::finish_set(&mut pazaak_match).expect("a set is in progress");
+        events.push(ReplayEvent::SetOver { winner });
+    }
+
+    let winner = pazaak_match.check_win();
+    events.push(ReplayEvent::MatchOver { winner });
+    (pazaak_match, events)
+}
+
+#[test]
+fn replays_play_back_to_the_recorded_result() {
+    for seed in 0..10 {
+        let (mut recorded, events) = record_match(seed);
+
+        let file: String = events.iter().map(|event| format!("{}\n", event)).collect();
+        let parsed = replay::read_replay(BufReader::new(file.as_bytes())).expect("valid replay");
+        assert_eq!(parsed.len(), events.len());
+
+        let mut replayer = Replayer::new();
+        for event in &parsed {
+            assert!(replayer.apply(event), "{}", event);
+        }
+        let mut replayed = replayer.pazaak_match.expect("a set was played");
+
+        assert_eq!(replayed.match_detail.score, recorded.match_detail.score);
+        assert_eq!(replayed.check_win(), recorded.check_win());
+        assert_eq!(replayed.games.len(), recorded.games.len());
+        for (replayed_game, recorded_game) in replayed.games.iter().zip(&recorded.games) {
+            assert_eq!(replayed_game.board, recorded_game.board);
+            assert_eq!(replayed_game.winner, recorded_game.winner);
+        }
+        for player in 0..2 {
+            assert_eq!(
+                replayed.players[player].hand.cards,
+                recorded.players[player].hand.cards
+            );
+            assert_eq!(
+                replayed.players[player].status,
+                recorded.players[player].status
+            );
+        }
+    }
+}
+
+#[test]
+fn plays_with_a_value_the_card_lacks_are_unparsable() {
+    let replay = |play: &str| {
+        let file = format!(
+            "pazaak-replay {}\nseed 1\nset 1\n{}\n",
+            REPLAY_VERSION, play
+        );
+        replay::read_replay(BufReader::new(file.as_bytes()))
+    };
+
+    assert!(replay("play 0 0 1 +1/-1").is_ok());
+    for play in ["play 0 0 2 +1/-1", "play 0 0 1 +3", "play 1 2 9 D"] {
+        match replay(play) {
+            Err(ReplayError::UnparsableLine { line_no, text }) => {
+                assert_eq!((line_no, text.as_str()), (4, play));
+            }
+            other => panic!(
+                "'{}' was read as {:?}",
+                play,
+                other.map(|events| events.len())
+            ),
+        }
+    }
+}
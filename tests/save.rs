@@ -0,0 +1,99 @@
+use pazaak::{
+    agent::BasicAgent,
+    deck::parse_deck,
+    engine::{self, start_set, start_turn},
+    save::{self, SaveError, SAVE_VERSION},
+    simulate, Action, GamePhase, Match, Status,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::{env, fs, path::PathBuf, process};
+
+const DECK: &str = "+2\n-3\n+1/-1\n4\n+1/-1T\n-1\n+/-1/2\n2&4\nD\n-2\n";
+
+fn save_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("pazaak-{}-{}.json", name, process::id()))
+}
+
+// A shuffled match in the middle of its first set, with the first player standing
+fn match_in_progress() -> Match {
+    let deck = parse_deck(DECK).expect("valid deck");
+    let mut rng = ChaCha12Rng::seed_from_u64(3);
+    let decks = [deck.shuffled(&mut rng), deck.shuffled(&mut rng)];
+    let [deck1, deck2] = decks;
+    let mut pazaak_match = Match::new(deck1, deck2, rng);
+
+    start_set(&mut pazaak_match).expect("no set has been dealt");
+    start_turn(&mut pazaak_match, 0).expect("the first player opens the set");
+    engine::apply_action(&mut pazaak_match, 0, Action::Stand).expect("standing is allowed");
+    start_turn(&mut pazaak_match, 1).expect("the second player's turn");
+    engine::apply_action(&mut pazaak_match, 1, Action::EndTurn).expect("ending is allowed");
+    pazaak_match
+}
+
+#[test]
+fn saved_matches_load_exactly_as_they_were() {
+    let pazaak_match = match_in_progress();
+    assert_eq!(pazaak_match.players[0].status, Status::Standing);
+    assert_eq!(pazaak_match.players[1].status, Status::Playing);
+
+    let path = save_path("round-trip");
+    save::save_match(&path, &pazaak_match).expect("the save is written");
+    let loaded = save::load_match(&path).expect("the save is read");
+    fs::remove_file(&path).expect("the save is removed");
+
+    assert_eq!(loaded.phase, GamePhase::Dealing);
+    assert_eq!(loaded.phase, pazaak_match.phase);
+    assert_eq!(loaded.next_player, pazaak_match.next_player);
+    assert_eq!(loaded.rules, pazaak_match.rules);
+    assert_eq!(loaded.match_detail.round, pazaak_match.match_detail.round);
+    assert_eq!(loaded.match_detail.score, pazaak_match.match_detail.score);
+    for player in 0..2 {
+        let (loaded, saved) = (&loaded.players[player], &pazaak_match.players[player]);
+        assert_eq!(loaded.status, saved.status);
+        assert_eq!(loaded.hand, saved.hand);
+        assert_eq!(loaded.deck, saved.deck);
+    }
+    assert_eq!(loaded.games.len(), 1);
+    let (loaded_game, saved_game) = (&loaded.games[0], &pazaak_match.games[0]);
+    assert_eq!(loaded_game.deck, saved_game.deck);
+    assert_eq!(loaded_game.board, saved_game.board);
+    assert_eq!(loaded_game.turn, saved_game.turn);
+    assert_eq!(loaded_game.played_card, saved_game.played_card);
+    assert_eq!(loaded.rng, pazaak_match.rng);
+
+    // The rest of the match, every later shuffle included, plays out the same
+    let agent = BasicAgent::default();
+    let [mut original, mut loaded] = [pazaak_match, loaded];
+    let finished = [&mut original, &mut loaded].map(|pazaak_match| {
+        let record = simulate::play_match(pazaak_match, [&agent, &agent]);
+        (record, pazaak_match.rng.clone())
+    });
+    assert!(finished[0].0.set_winners.len() > 1);
+    assert_eq!(finished[0], finished[1]);
+    assert_eq!(original.games.len(), loaded.games.len());
+    for (original, loaded) in original.games.iter().zip(&loaded.games) {
+        assert_eq!(original.board, loaded.board);
+    }
+}
+
+#[test]
+fn saves_from_other_versions_are_rejected() {
+    let path = save_path("old-version");
+    save::save_match(&path, &match_in_progress()).expect("the save is written");
+    let content = fs::read_to_string(&path).expect("the save is read");
+    let old = content.replacen(
+        &format!("\"version\": {}", SAVE_VERSION),
+        &format!("\"version\": {}", SAVE_VERSION - 1),
+        1,
+    );
+    assert_ne!(old, content);
+    fs::write(&path, old).expect("the save is rewritten");
+
+    let loaded = save::load_match(&path);
+    fs::remove_file(&path).expect("the save is removed");
+    assert!(matches!(
+        loaded,
+        Err(SaveError::UnsupportedVersion(version)) if version == SAVE_VERSION - 1
+    ));
+}
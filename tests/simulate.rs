@@ -0,0 +1,98 @@
+use pazaak::{
+    deck::parse_deck,
+    engine,
+    simulate::{MatchRecord, Simulation},
+    Action, Agent, PlayerView, Rules,
+};
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use std::cell::RefCell;
+
+const DECK: &str = "+2\n-3\n+1/-1\n4\n+1/-1T\n-1\n+/-1/2\n2&4\nD\n-2\n";
+
+#[test]
+fn thread_count_does_not_change_the_results() {
+    let deck = parse_deck(DECK).expect("valid deck");
+    let simulation = |threads| Simulation {
+        decks: [deck.clone(), deck.clone()],
+        agents: ["ai:basic".to_string(), "ai:basic".to_string()],
+        rules: Rules::default(),
+        games: 40,
+        seed: 1234,
+        threads,
+    };
+
+    let single = simulation(1).run().expect("built-in agents");
+    assert_eq!(single.matches, 40);
+    assert_eq!(single.winners.len(), 40);
+    for threads in [2, 3, 8, 64] {
+        assert_eq!(simulation(threads).run().expect("built-in agents"), single);
+    }
+
+    // A different seed shuffles differently
+    let reseeded = Simulation {
+        seed: 4321,
+        ..simulation(1)
+    };
+    assert_ne!(reseeded.run().expect("built-in agents"), single);
+}
+
+// Picks any legal action at random
+struct RandomAgent {
+    rng: RefCell<ChaCha12Rng>,
+}
+
+impl RandomAgent {
+    fn new() -> RandomAgent {
+        RandomAgent {
+            rng: RefCell::new(ChaCha12Rng::seed_from_u64(0)),
+        }
+    }
+}
+
+impl Agent for RandomAgent {
+    fn name(&self) -> &str {
+        "test:random"
+    }
+
+    fn choose_action(&self, view: &PlayerView) -> Action {
+        *engine::legal_actions(view)
+            .choose(&mut *self.rng.borrow_mut())
+            .expect("standing is always legal")
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        *self.rng.get_mut() = ChaCha12Rng::seed_from_u64(seed);
+    }
+}
+
+#[test]
+fn random_agents_play_a_match_the_same_way_whatever_came_before() {
+    let deck = parse_deck(DECK).expect("valid deck");
+    let simulation = Simulation {
+        decks: [deck.clone(), deck],
+        agents: ["test:random".to_string(), "test:random".to_string()],
+        rules: Rules::default(),
+        games: 10,
+        seed: 99,
+        threads: 1,
+    };
+    let play = |index, agents: &mut [RandomAgent; 2]| -> MatchRecord {
+        let [agent1, agent2] = agents;
+        simulation.play_game(index, [agent1, agent2])
+    };
+
+    // Match 9 straight away, and after the agents have played every match before it
+    let fresh = play(9, &mut [RandomAgent::new(), RandomAgent::new()]);
+    let mut agents = [RandomAgent::new(), RandomAgent::new()];
+    let records: Vec<MatchRecord> = (0..10).map(|index| play(index, &mut agents)).collect();
+    assert_eq!(records[9], fresh);
+
+    // The agents really are random, and the seats don't mirror each other
+    assert!(records
+        .windows(2)
+        .any(|pair| pair[0].played != pair[1].played));
+    assert!(records
+        .iter()
+        .any(|record| record.played[0] != record.played[1]));
+}
@@ -0,0 +1,227 @@
+use pazaak::{
+    agent,
+    solver::{self, Position, PositionError},
+    Action, HandPolicy, PlayerView, Rules,
+};
+
+fn view(position: &str, rules: Rules) -> PlayerView {
+    Position::parse(position)
+        .and_then(|position| position.to_view(rules))
+        .expect("valid position")
+}
+
+fn win_chance(view: &PlayerView, action: Action) -> f64 {
+    solver::solve(view)
+        .actions
+        .into_iter()
+        .find(|&(listed, _)| listed == action)
+        .map(|(_, win_chance)| win_chance)
+        .expect("the action is open")
+}
+
+const PLAY: Action = Action::Play {
+    card_index: 0,
+    value_index: 0,
+};
+
+fn assert_close(found: f64, expected: f64) {
+    assert!(
+        (found - expected).abs() < 1e-9,
+        "expected {}, found {}",
+        expected,
+        found
+    );
+}
+
+#[test]
+fn a_higher_total_stands() {
+    let view = view(
+        r#"
+        board = ["10", "9"]
+        hand = []
+        opponent_board = ["10", "8"]
+        opponent_standing = true
+        "#,
+        Rules::default(),
+    );
+    let solution = solver::solve(&view);
+
+    assert_eq!(solution.best(), Action::Stand);
+    assert_close(win_chance(&view, Action::Stand), 1.0);
+}
+
+#[test]
+fn a_tie_draws_for_the_only_winning_card() {
+    // 19 each and nothing in hand: standing ties, and only a 1 of the 36 cards left in
+    // the board deck wins
+    let view = view(
+        r#"
+        board = ["10", "9"]
+        hand = []
+        opponent_board = ["9", "10"]
+        opponent_standing = true
+        "#,
+        Rules::default(),
+    );
+    let solution = solver::solve(&view);
+
+    assert_eq!(solution.best(), Action::EndTurn);
+    assert_close(win_chance(&view, Action::EndTurn), 4.0 / 36.0);
+    assert_close(win_chance(&view, Action::Stand), 0.0);
+}
+
+#[test]
+fn a_tiebreaker_wins_the_tie() {
+    let view = view(
+        r#"
+        board = ["10", "10", "+1/-1T=-1"]
+        hand = []
+        opponent_board = ["9", "10"]
+        opponent_standing = true
+        "#,
+        Rules::default(),
+    );
+
+    assert_eq!(solver::solve(&view).best(), Action::Stand);
+    assert_close(win_chance(&view, Action::Stand), 1.0);
+}
+
+#[test]
+fn a_rescue_card_is_the_only_way_back() {
+    let view = view(
+        r#"
+        board = ["10", "8", "5"]
+        hand = ["+2", "-3"]
+        opponent_board = ["10", "9"]
+        opponent_standing = true
+        "#,
+        Rules::default(),
+    );
+
+    let rescue = Action::Play {
+        card_index: 1,
+        value_index: 0,
+    };
+    assert_eq!(solver::solve(&view).best(), rescue);
+    assert_close(win_chance(&view, rescue), 1.0);
+    for action in [Action::Stand, Action::EndTurn, PLAY] {
+        assert_close(win_chance(&view, action), 0.0);
+    }
+}
+
+#[test]
+fn card_cost_ranks_without_changing_win_chances() {
+    // Playing the +1 wins outright, while drawing wins one time in nine
+    let position = r#"
+        board = ["10", "9"]
+        hand = ["+1"]
+        opponent_board = ["9", "10"]
+        opponent_standing = true
+    "#;
+
+    for hand_policy in [HandPolicy::KeepForMatch, HandPolicy::DealEachSet] {
+        let view = view(
+            position,
+            Rules {
+                hand_policy,
+                ..Rules::default()
+            },
+        );
+        let solution = solver::solve(&view);
+
+        assert_eq!(solution.best(), PLAY, "{:?}", hand_policy);
+        assert_close(solution.actions[0].1, 1.0);
+        assert_close(win_chance(&view, Action::EndTurn), 4.0 / 36.0);
+    }
+}
+
+#[test]
+fn card_cost_saves_cards_that_barely_help() {
+    // Playing the +1 wins more often than standing on 16, but not by enough to spend
+    // a card the rest of the match could use
+    let position = r#"
+        board = ["10", "6"]
+        hand = ["+1"]
+        opponent_board = ["10", "3"]
+    "#;
+    let rules = |hand_policy| Rules {
+        hand_policy,
+        ..Rules::default()
+    };
+
+    let keep = view(position, rules(HandPolicy::KeepForMatch));
+    let ranked: Vec<Action> = solver::solve(&keep)
+        .actions
+        .iter()
+        .map(|&(action, _)| action)
+        .collect();
+    assert_eq!(ranked, [Action::EndTurn, Action::Stand, PLAY]);
+    assert!(win_chance(&keep, PLAY) > win_chance(&keep, Action::Stand));
+
+    // With a new hand every set, actions are ranked by their win chance alone
+    let deal = view(position, rules(HandPolicy::DealEachSet));
+    let chances: Vec<f64> = solver::solve(&deal)
+        .actions
+        .iter()
+        .map(|&(_, win_chance)| win_chance)
+        .collect();
+    assert!(chances.windows(2).all(|pair| pair[0] >= pair[1]));
+    assert_close(win_chance(&deal, PLAY), win_chance(&keep, PLAY));
+}
+
+#[test]
+fn solving_follows_the_view_rules() {
+    // Eight cards worth 2 in all, so any ninth fills the table without going over 20
+    let position = r#"
+        board = ["1", "1", "1", "1", "-3", "-3", "2", "2"]
+        hand = []
+        opponent_board = ["10", "9"]
+        opponent_standing = true
+    "#;
+
+    let filling = view(position, Rules::default());
+    assert_eq!(solver::solve(&filling).best(), Action::EndTurn);
+    assert_close(win_chance(&filling, Action::EndTurn), 1.0);
+
+    let unfilled = view(
+        position,
+        Rules {
+            fill_the_table: false,
+            ..Rules::default()
+        },
+    );
+    assert!(win_chance(&unfilled, Action::EndTurn) < 1.0);
+
+    // The agent plans with the rules it finds in the view
+    let solver_agent = agent::from_name("ai:solver").expect("built-in agent");
+    for view in [filling, unfilled] {
+        assert_eq!(
+            solver_agent.choose_action(&view),
+            solver::solve(&view).best()
+        );
+    }
+}
+
+#[test]
+fn oversized_hands_are_rejected() {
+    let position = Position::parse(
+        r#"
+        board = ["5"]
+        hand = ["+1", "+2", "+3", "+4", "+5"]
+        opponent_board = ["5"]
+        "#,
+    )
+    .expect("valid position");
+
+    assert!(matches!(
+        position.to_view(Rules::default()),
+        Err(PositionError::HandTooLarge(5))
+    ));
+
+    let mut position = position;
+    position.hand = vec!["+1".to_string(); 16];
+    assert!(matches!(
+        position.to_view(Rules::default()),
+        Err(PositionError::HandTooLarge(16))
+    ));
+}
@@ -0,0 +1,267 @@
+use pazaak::{
+    deck::parse_deck,
+    tournament::{
+        Entrant, MatchResult, Tournament, TournamentConfig, TournamentFormat, ELO_K, STARTING_ELO,
+    },
+    Rules,
+};
+use std::collections::HashSet;
+
+const DECK: &str = "+2\n-3\n+1/-1\n4\n+1/-1T\n-1\n+/-1/2\n2&4\nD\n-2\n";
+
+fn new_tournament(format: TournamentFormat, entrants: usize, rounds: Option<usize>) -> Tournament {
+    let deck = parse_deck(DECK).expect("valid deck");
+
+    Tournament {
+        config: TournamentConfig {
+            name: None,
+            format,
+            rounds,
+            seed: 77,
+            rules: Rules::default(),
+            entrants: (0..entrants)
+                .map(|entrant| Entrant {
+                    name: format!("Entrant {}", entrant),
+                    deck: "deck.pzk".to_string(),
+                    agent: "ai:basic".to_string(),
+                })
+                .collect(),
+        },
+        decks: vec![deck; entrants],
+        results: vec![],
+    }
+}
+
+fn run(mut tournament: Tournament) -> Tournament {
+    tournament.run(|_, _| Ok(())).expect("nothing to save");
+    tournament
+}
+
+fn matches(tournament: &Tournament) -> Vec<(usize, usize)> {
+    tournament
+        .results
+        .iter()
+        .filter_map(|result| Some((result.home, result.away?)))
+        .collect()
+}
+
+// Every pair of entrants, smallest first, once for each time they met
+fn meetings(tournament: &Tournament) -> Vec<(usize, usize)> {
+    let mut meetings: Vec<(usize, usize)> = matches(tournament)
+        .into_iter()
+        .map(|(home, away)| (home.min(away), home.max(away)))
+        .collect();
+    meetings.sort();
+    meetings
+}
+
+fn rounds(tournament: &Tournament) -> usize {
+    tournament
+        .results
+        .iter()
+        .map(|result| result.round)
+        .max()
+        .unwrap_or(0)
+}
+
+// Nobody is scheduled twice in the same round
+fn assert_one_match_per_round(tournament: &Tournament) {
+    for round in 1..=rounds(tournament) {
+        let mut scheduled = HashSet::new();
+        for result in tournament
+            .results
+            .iter()
+            .filter(|result| result.round == round)
+        {
+            for entrant in [Some(result.home), result.away].into_iter().flatten() {
+                assert!(
+                    scheduled.insert(entrant),
+                    "entrant {} plays twice in round {}",
+                    entrant,
+                    round
+                );
+            }
+        }
+    }
+}
+
+fn assert_close(found: f64, expected: f64) {
+    assert!(
+        (found - expected).abs() < 1e-9,
+        "expected {}, found {}",
+        expected,
+        found
+    );
+}
+
+#[test]
+fn round_robins_pair_everyone_once() {
+    for entrants in [2, 5, 6, 8] {
+        let tournament = run(new_tournament(TournamentFormat::RoundRobin, entrants, None));
+
+        let every_pair: Vec<(usize, usize)> = (0..entrants)
+            .flat_map(|a| (a + 1..entrants).map(move |b| (a, b)))
+            .collect();
+        assert_eq!(meetings(&tournament), every_pair, "{} entrants", entrants);
+        assert_eq!(rounds(&tournament), entrants + entrants % 2 - 1);
+        assert_one_match_per_round(&tournament);
+
+        // Sitting a round out isn't a bye, so it isn't recorded as a result
+        assert!(tournament
+            .results
+            .iter()
+            .all(|result| result.away.is_some()));
+
+        // Seats alternate, so nobody plays first much more often than second
+        for entrant in 0..entrants {
+            let home = matches(&tournament)
+                .iter()
+                .filter(|&&(home, _)| home == entrant)
+                .count();
+            let away = entrants - 1 - home;
+            assert!(
+                home.abs_diff(away) <= 1,
+                "entrant {} of {} plays first {} times and second {} times",
+                entrant,
+                entrants,
+                home,
+                away
+            );
+        }
+    }
+}
+
+#[test]
+fn swiss_rounds_avoid_rematches() {
+    // Four entrants over three rounds have to meet everyone exactly once
+    let tournament = run(new_tournament(TournamentFormat::Swiss, 4, Some(3)));
+    assert_eq!(
+        meetings(&tournament),
+        [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]
+    );
+    assert_one_match_per_round(&tournament);
+
+    for entrants in [7, 8, 12] {
+        let tournament = run(new_tournament(TournamentFormat::Swiss, entrants, Some(4)));
+        let meetings = meetings(&tournament);
+
+        assert_eq!(rounds(&tournament), 4);
+        assert_one_match_per_round(&tournament);
+        assert_eq!(
+            meetings.iter().collect::<HashSet<_>>().len(),
+            meetings.len(),
+            "a rematch among {} entrants",
+            entrants
+        );
+
+        // Byes go to a different entrant each round
+        let byes: Vec<usize> = tournament
+            .results
+            .iter()
+            .filter(|result| result.away.is_none())
+            .map(|result| result.home)
+            .collect();
+        assert_eq!(byes.len(), 4 * (entrants % 2));
+        assert_eq!(byes.iter().collect::<HashSet<_>>().len(), byes.len());
+    }
+}
+
+#[test]
+fn swiss_rounds_default_to_finding_one_unbeaten_entrant() {
+    let tournament = run(new_tournament(TournamentFormat::Swiss, 8, None));
+    assert_eq!(rounds(&tournament), 3);
+
+    let tournament = run(new_tournament(TournamentFormat::Swiss, 5, None));
+    assert_eq!(rounds(&tournament), 3);
+}
+
+#[test]
+fn brackets_keep_the_top_seeds_apart() {
+    let tournament = run(new_tournament(TournamentFormat::SingleElimination, 8, None));
+    let first_round: Vec<(usize, usize)> = matches(&tournament).into_iter().take(4).collect();
+    assert_eq!(first_round, [(0, 7), (3, 4), (1, 6), (2, 5)]);
+
+    // Each round halves the field until one entrant is left
+    let round_sizes: Vec<usize> = (1..=rounds(&tournament))
+        .map(|round| {
+            tournament
+                .results
+                .iter()
+                .filter(|result| result.round == round)
+                .count()
+        })
+        .collect();
+    assert_eq!(round_sizes, [4, 2, 1]);
+}
+
+#[test]
+fn byes_go_to_the_top_seeds() {
+    let tournament = run(new_tournament(TournamentFormat::SingleElimination, 6, None));
+    let first_round: Vec<(usize, Option<usize>)> = tournament
+        .results
+        .iter()
+        .filter(|result| result.round == 1)
+        .map(|result| (result.home, result.away))
+        .collect();
+
+    assert_eq!(
+        first_round,
+        [(0, None), (3, Some(4)), (1, None), (2, Some(5))]
+    );
+    assert_eq!(rounds(&tournament), 3);
+    assert_eq!(matches(&tournament).len(), 5);
+}
+
+#[test]
+fn elo_moves_by_the_surprise_of_the_result() {
+    let mut tournament = new_tournament(TournamentFormat::RoundRobin, 3, None);
+    let result = |home, away, winner, score| MatchResult {
+        round: 1,
+        home,
+        away: Some(away),
+        winner,
+        score,
+    };
+    let elo = |tournament: &Tournament, entrant| {
+        tournament
+            .standings()
+            .into_iter()
+            .find(|standing| standing.entrant == entrant)
+            .expect("every entrant has a standing")
+            .elo
+    };
+
+    // Evenly rated entrants were expected to score half a point each
+    tournament.results = vec![result(0, 1, Some(0), [3, 1])];
+    assert_close(elo(&tournament, 0), STARTING_ELO + ELO_K / 2.0);
+    assert_close(elo(&tournament, 1), STARTING_ELO - ELO_K / 2.0);
+    assert_close(elo(&tournament, 2), STARTING_ELO);
+
+    // A draw against the weaker entrant costs the stronger one rating
+    tournament.results.push(result(1, 0, None, [2, 2]));
+    let expected = 1.0 / (1.0 + 10f64.powf(ELO_K / 400.0));
+    let change = ELO_K * (0.5 - expected);
+    assert!(change > 0.0);
+    assert_close(elo(&tournament, 0), STARTING_ELO + ELO_K / 2.0 - change);
+    assert_close(elo(&tournament, 1), STARTING_ELO - ELO_K / 2.0 + change);
+
+    // A bye is a win that leaves Elo alone
+    tournament.results.push(MatchResult {
+        round: 2,
+        home: 2,
+        away: None,
+        winner: Some(2),
+        score: [0, 0],
+    });
+    let standings = tournament.standings();
+    let bye = standings
+        .iter()
+        .find(|standing| standing.entrant == 2)
+        .expect("every entrant has a standing");
+    assert_eq!((bye.played, bye.wins), (1, 1));
+    assert_close(bye.elo, STARTING_ELO);
+
+    // Rating is zero-sum
+    let total: f64 = standings.iter().map(|standing| standing.elo).sum();
+    assert_close(total, 3.0 * STARTING_ELO);
+}
@@ -0,0 +1,14 @@
+use pazaak::util::parse_option_number;
+
+#[test]
+fn options_are_numbered_from_1() {
+    // A ±1/2 card has four values, listed 1 to 4
+    assert_eq!(parse_option_number("1", 4), Some(0));
+    assert_eq!(parse_option_number("4", 4), Some(3));
+    assert_eq!(parse_option_number(" 2\n", 4), Some(1));
+
+    for input in ["0", "5", "-1", "", "two", "1.5", "99999999999999999999999"] {
+        assert_eq!(parse_option_number(input, 4), None, "{:?}", input);
+    }
+    assert_eq!(parse_option_number("1", 0), None);
+}
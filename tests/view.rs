@@ -0,0 +1,96 @@
+use pazaak::{
+    deck::parse_deck,
+    engine::{self, start_set, start_turn},
+    Action, Match, PlayerView,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+const DECKS: [&str; 2] = [
+    "+1\n+2\n+3\n+4\n+5\n-1\n-2\n-3\n-4\n-5\n",
+    "+1/-1\n+2/-2\n+3/-3\n+4/-4\n+5/-5\n+6/-6\n2&4\n3&6\nD\n+1/-1T\n",
+];
+
+// A shuffled match a few turns into its first set
+fn match_in_progress() -> Match {
+    let mut rng = ChaCha12Rng::seed_from_u64(12);
+    let decks = DECKS.map(|deck| parse_deck(deck).expect("valid deck").shuffled(&mut rng));
+    let [deck1, deck2] = decks;
+    let mut pazaak_match = Match::new(deck1, deck2, rng);
+
+    start_set(&mut pazaak_match).expect("no set has been dealt");
+    for player in [0, 1, 0] {
+        start_turn(&mut pazaak_match, player).expect("the player's turn");
+        engine::apply_action(&mut pazaak_match, player, Action::EndTurn)
+            .expect("ending a turn is always allowed");
+    }
+    pazaak_match
+}
+
+#[test]
+fn views_only_hold_what_the_seat_may_see() {
+    let pazaak_match = match_in_progress();
+    let game = pazaak_match.games.last().expect("a set is in progress");
+
+    for seat in [0, 1] {
+        let opponent = 1 - seat;
+        let view = PlayerView::new(&pazaak_match, seat);
+
+        assert_eq!(view.seat, seat);
+        assert_eq!(view.hand.len(), 4);
+        assert_eq!(view.hand, pazaak_match.players[seat].hand.cards);
+        assert_eq!(
+            view.opponent_hand_size,
+            pazaak_match.players[opponent].hand.cards.len()
+        );
+        assert_eq!(view.board, game.board[seat]);
+        assert_eq!(view.opponent_board, game.board[opponent]);
+        assert_eq!(view.deck_remaining, game.deck.cards.len());
+        assert_eq!(view.deck_remaining, 40 - 3);
+    }
+
+    // The two seats' hands come from different side decks, so a leak would show up here
+    let views = [0, 1].map(|seat| PlayerView::new(&pazaak_match, seat));
+    for card in &views[0].hand {
+        assert!(!views[1].hand.contains(card));
+    }
+}
+
+#[test]
+fn views_carry_no_hidden_cards() {
+    let pazaak_match = match_in_progress();
+
+    // Whatever a view is turned into, e.g. to be sent over the network, only counts of
+    // the hidden cards go with it
+    for seat in [0, 1] {
+        let view =
+            serde_json::to_value(PlayerView::new(&pazaak_match, seat)).expect("views serialize");
+        let mut fields: Vec<&str> = view
+            .as_object()
+            .expect("a view is a struct")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        fields.sort();
+
+        assert_eq!(
+            fields,
+            [
+                "board",
+                "deck_remaining",
+                "hand",
+                "opponent_board",
+                "opponent_hand_size",
+                "opponent_status",
+                "played_card",
+                "round",
+                "rules",
+                "score",
+                "seat",
+                "status",
+            ]
+        );
+        assert!(view["opponent_hand_size"].is_u64());
+        assert!(view["deck_remaining"].is_u64());
+    }
+}